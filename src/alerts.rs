@@ -0,0 +1,570 @@
+use crate::snapshot::Snapshot;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    #[serde(flatten)]
+    pub condition: Condition,
+    // How long the condition has to hold before the alert fires
+    #[serde(default)]
+    pub for_secs: u64,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(tag = "metric", rename_all = "snake_case")]
+pub enum Condition {
+    Cpu {
+        above: f32,
+    },
+    Memory {
+        above: f32,
+    },
+    Mount {
+        mount: String,
+        above: f32,
+    },
+    ProcessAbsent {
+        process: String,
+    },
+    ProcessRss {
+        process: Option<String>,
+        #[serde(deserialize_with = "deserialize_size")]
+        above: u64,
+    },
+    ProcessCpu {
+        process: Option<String>,
+        above: f32,
+    },
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    Command { command: String },
+    Log { path: PathBuf },
+    Webhook { url: String },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SizeValue {
+    Bytes(u64),
+    Text(String),
+}
+
+// Accepts plain byte counts or strings such as "512MB" and "2 GiB"
+pub fn parse_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: f64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1e3,
+        "m" | "mb" => 1e6,
+        "g" | "gb" => 1e9,
+        "t" | "tb" => 1e12,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * multiplier) as u64)
+}
+
+fn deserialize_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    match SizeValue::deserialize(deserializer)? {
+        SizeValue::Bytes(bytes) => Ok(bytes),
+        SizeValue::Text(text) => parse_size(&text)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid size \"{}\"", text))),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AlertState {
+    Ok,
+    Pending(Instant),
+    Firing(Instant),
+}
+
+pub struct Alert {
+    pub rule: Rule,
+    pub state: AlertState,
+    pub message: String,
+    // Why the condition could not be checked on the latest refresh
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct Notification {
+    pub rule: String,
+    pub state: &'static str,
+    pub message: String,
+    pub host: String,
+    pub timestamp: u64,
+}
+
+pub struct AlertEngine {
+    pub alerts: Vec<Alert>,
+    pub last_error: Option<String>,
+    host: String,
+    errors_tx: Sender<String>,
+    errors_rx: Receiver<String>,
+}
+
+fn matches_name(filter: &Option<String>, name: &str) -> bool {
+    match filter {
+        Some(filter) => filter == name,
+        None => true,
+    }
+}
+
+// Returns a description of the breach, or None if the condition is not met.
+// Errors when the snapshot has nothing to check the condition against.
+pub fn check_condition(
+    condition: &Condition,
+    snapshot: &Snapshot,
+) -> Result<Option<String>, String> {
+    let breach = match condition {
+        Condition::Cpu { above } => {
+            let usage = snapshot.stats.cpu_global_usage;
+            (usage > *above).then(|| format!("global CPU {:.1}% > {}%", usage, above))
+        }
+        Condition::Memory { above } => {
+            let stats = &snapshot.stats;
+            let usage = (stats.used_memory as f64 / stats.total_memory as f64 * 100.0) as f32;
+            (usage > *above).then(|| format!("memory {:.1}% > {}%", usage, above))
+        }
+        Condition::Mount { mount, above } => {
+            let disk = snapshot
+                .disks
+                .iter()
+                .find(|d| &d.mount_point == mount || d.bind_mounts.contains(mount))
+                .ok_or_else(|| {
+                    format!("mount {} not found or hidden by the [disks] filter", mount)
+                })?;
+            let usage = disk.usage();
+            (usage > *above).then(|| format!("mount {} usage {:.1}% > {}%", mount, usage, above))
+        }
+        Condition::ProcessAbsent { process } => {
+            let present = snapshot
+                .processes
+                .iter()
+                .any(|p| !p.is_thread && &p.name == process);
+            (!present).then(|| format!("process {} is not running", process))
+        }
+        Condition::ProcessRss { process, above } => snapshot
+            .processes
+            .iter()
+            .filter(|p| !p.is_thread && matches_name(process, &p.name) && p.memory > *above)
+            .max_by_key(|p| p.memory)
+            .map(|p| {
+                format!(
+                    "process {} ({}) RSS {:.2} MB > {:.2} MB",
+                    p.name,
+                    p.pid,
+                    p.memory as f64 / 1000000.0,
+                    *above as f64 / 1000000.0
+                )
+            }),
+        Condition::ProcessCpu { process, above } => snapshot
+            .processes
+            .iter()
+            .filter(|p| !p.is_thread && matches_name(process, &p.name) && p.cpu > *above)
+            .max_by(|a, b| a.cpu.total_cmp(&b.cpu))
+            .map(|p| {
+                format!(
+                    "process {} ({}) CPU {:.1}% > {}%",
+                    p.name, p.pid, p.cpu, above
+                )
+            }),
+    };
+    Ok(breach)
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<Rule>, host: String) -> AlertEngine {
+        let (errors_tx, errors_rx) = mpsc::channel();
+        AlertEngine {
+            alerts: rules
+                .into_iter()
+                .map(|rule| Alert {
+                    rule,
+                    state: AlertState::Ok,
+                    message: String::new(),
+                    error: None,
+                })
+                .collect(),
+            last_error: None,
+            host,
+            errors_tx,
+            errors_rx,
+        }
+    }
+
+    pub fn set_host(&mut self, host: &str) {
+        if self.host != host {
+            self.host = host.to_string();
+        }
+    }
+
+    pub fn evaluate(&mut self, snapshot: &Snapshot, now: Instant) {
+        let mut notifications = Vec::new();
+        for alert in self.alerts.iter_mut() {
+            let breach = match check_condition(&alert.rule.condition, snapshot) {
+                Ok(breach) => {
+                    alert.error = None;
+                    breach
+                }
+                Err(error) => {
+                    alert.error = Some(error);
+                    None
+                }
+            };
+            let hold = Duration::from_secs(alert.rule.for_secs);
+            let next_state = match (alert.state, &breach) {
+                (AlertState::Ok, Some(_)) => AlertState::Pending(now),
+                (AlertState::Pending(since), Some(_)) => AlertState::Pending(since),
+                (AlertState::Firing(since), Some(_)) => AlertState::Firing(since),
+                (_, None) => AlertState::Ok,
+            };
+            let next_state = match next_state {
+                AlertState::Pending(since) if now.duration_since(since) >= hold => {
+                    AlertState::Firing(since)
+                }
+                state => state,
+            };
+
+            let fired = matches!(next_state, AlertState::Firing(_))
+                && !matches!(alert.state, AlertState::Firing(_));
+            let resolved =
+                matches!(alert.state, AlertState::Firing(_)) && next_state == AlertState::Ok;
+            if let Some(message) = breach {
+                alert.message = message;
+            }
+            alert.state = next_state;
+
+            if fired || resolved {
+                let notification = Notification {
+                    rule: alert.rule.name.clone(),
+                    state: if fired { "firing" } else { "resolved" },
+                    message: alert.message.clone(),
+                    host: self.host.clone(),
+                    timestamp: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0),
+                };
+                notifications.push((alert.rule.actions.clone(), notification));
+            }
+        }
+
+        for (actions, notification) in notifications {
+            for action in actions {
+                self.dispatch(action, &notification);
+            }
+        }
+        while let Ok(error) = self.errors_rx.try_recv() {
+            self.last_error = Some(error);
+        }
+    }
+
+    // Actions may block on the network or a slow command, so each runs on its
+    // own thread and reports failures back through the channel.
+    fn dispatch(&self, action: Action, notification: &Notification) {
+        let payload = serde_json::to_string(notification).unwrap_or_default();
+        let rule = notification.rule.clone();
+        let state = notification.state;
+        let line = format!(
+            "{} {} {} {}: {}\n",
+            notification.timestamp,
+            notification.host,
+            state.to_uppercase(),
+            rule,
+            notification.message
+        );
+        let message = notification.message.clone();
+        let errors_tx = self.errors_tx.clone();
+        thread::spawn(move || {
+            let result = match action {
+                Action::Command { command } => run_command(&command, &rule, state, &message),
+                Action::Log { path } => append_log(&path, &line),
+                Action::Webhook { url } => post_json(&url, &payload),
+            };
+            if let Err(error) = result {
+                let _ = errors_tx.send(format!("{}: {}", rule, error));
+            }
+        });
+    }
+}
+
+fn run_command(command: &str, rule: &str, state: &str, message: &str) -> Result<(), String> {
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("ALERT_RULE", rule)
+        .env("ALERT_STATE", state)
+        .env("ALERT_MESSAGE", message)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("command failed to start: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("command exited with {}", status))
+    }
+}
+
+fn append_log(path: &PathBuf, line: &str) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("cannot open {}: {}", path.display(), e))?;
+    file.write_all(line.as_bytes())
+        .map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+// Minimal HTTP/1.1 client: plain http:// only, one request per connection
+pub fn post_json(url: &str, body: &str) -> Result<(), String> {
+    let rest = url.strip_prefix("http://").ok_or_else(|| {
+        format!(
+            "unsupported webhook URL {} (only http:// is supported)",
+            url
+        )
+    })?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let address = if authority.rsplit(']').next().unwrap_or("").contains(':') {
+        authority.to_string()
+    } else {
+        format!("{}:80", authority)
+    };
+    let address = address
+        .to_socket_addrs()
+        .map_err(|e| format!("cannot resolve {}: {}", authority, e))?
+        .next()
+        .ok_or_else(|| format!("cannot resolve {}", authority))?;
+
+    let mut stream = TcpStream::connect_timeout(&address, WEBHOOK_TIMEOUT)
+        .map_err(|e| format!("cannot connect to {}: {}", authority, e))?;
+    let _ = stream.set_read_timeout(Some(WEBHOOK_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(WEBHOOK_TIMEOUT));
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        authority,
+        body.len(),
+        body
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("webhook request failed: {}", e))?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut status_line)
+        .map_err(|e| format!("webhook response failed: {}", e))?;
+    let code = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| format!("malformed webhook response {:?}", status_line.trim()))?;
+    if (200..300).contains(&code) {
+        Ok(())
+    } else {
+        Err(format!("webhook returned HTTP {}", code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disks::DiskInfo;
+    use std::env;
+    use std::fs;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::process;
+
+    // Answers one request with `status` and hands back what it received
+    fn http_stub(status: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hooks/alert", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.strip_prefix("Content-Length: ") {
+                    length = value.trim().parse().unwrap();
+                }
+                head.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status);
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            head + &String::from_utf8(body).unwrap()
+        });
+        (url, handle)
+    }
+
+    fn cpu_rule(actions: Vec<Action>) -> Rule {
+        Rule {
+            name: String::from("busy"),
+            condition: Condition::Cpu { above: 80.0 },
+            for_secs: 10,
+            actions,
+        }
+    }
+
+    fn snapshot(cpu: f32) -> Snapshot {
+        let mut snapshot = Snapshot::default();
+        snapshot.stats.cpu_global_usage = cpu;
+        snapshot
+    }
+
+    #[test]
+    fn webhook_posts_json() {
+        let (url, stub) = http_stub("200 OK");
+        let body = r#"{"rule":"busy","state":"firing"}"#;
+        assert_eq!(post_json(&url, body), Ok(()));
+
+        let request = stub.join().unwrap();
+        assert!(request.starts_with("POST /hooks/alert HTTP/1.1\r\n"));
+        assert!(request.contains("Content-Type: application/json\r\n"));
+        assert!(request.contains(&format!("Content-Length: {}\r\n", body.len())));
+        assert!(request.ends_with(&format!("\r\n\r\n{}", body)));
+    }
+
+    #[test]
+    fn webhook_reports_http_errors() {
+        let (url, stub) = http_stub("500 Internal Server Error");
+        let result = post_json(&url, "{}");
+        stub.join().unwrap();
+        assert_eq!(result, Err(String::from("webhook returned HTTP 500")));
+    }
+
+    #[test]
+    fn webhook_rejects_https() {
+        assert!(post_json("https://example.org/", "{}").is_err());
+    }
+
+    #[test]
+    fn fires_after_holding_and_resolves() {
+        let log = env::temp_dir().join(format!("resource-manager-alerts-{}.log", process::id()));
+        let _ = fs::remove_file(&log);
+        let rule = cpu_rule(vec![Action::Log { path: log.clone() }]);
+        let mut engine = AlertEngine::new(vec![rule], String::from("test-host"));
+        let start = Instant::now();
+
+        engine.evaluate(&snapshot(10.0), start);
+        assert!(engine.alerts[0].state == AlertState::Ok);
+
+        engine.evaluate(&snapshot(95.0), start + Duration::from_secs(1));
+        let pending = AlertState::Pending(start + Duration::from_secs(1));
+        assert!(engine.alerts[0].state == pending);
+        assert_eq!(engine.alerts[0].message, "global CPU 95.0% > 80%");
+
+        // Still short of for_secs
+        engine.evaluate(&snapshot(92.0), start + Duration::from_secs(5));
+        assert!(engine.alerts[0].state == pending);
+
+        engine.evaluate(&snapshot(90.0), start + Duration::from_secs(11));
+        let firing = AlertState::Firing(start + Duration::from_secs(1));
+        assert!(engine.alerts[0].state == firing);
+
+        engine.evaluate(&snapshot(20.0), start + Duration::from_secs(12));
+        assert!(engine.alerts[0].state == AlertState::Ok);
+
+        // Actions run on their own threads
+        let mut lines = Vec::new();
+        for _ in 0..50 {
+            let contents = fs::read_to_string(&log).unwrap_or_default();
+            lines = contents.lines().map(String::from).collect::<Vec<_>>();
+            if lines.len() == 2 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        let _ = fs::remove_file(&log);
+        lines.sort_by_key(|line| line.contains("RESOLVED"));
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("test-host FIRING busy: global CPU 90.0% > 80%"));
+        assert!(lines[1].ends_with("test-host RESOLVED busy: global CPU 90.0% > 80%"));
+    }
+
+    #[test]
+    fn pending_clears_without_firing() {
+        let mut engine = AlertEngine::new(vec![cpu_rule(Vec::new())], String::new());
+        let start = Instant::now();
+        engine.evaluate(&snapshot(95.0), start);
+        engine.evaluate(&snapshot(10.0), start + Duration::from_secs(3));
+        assert!(engine.alerts[0].state == AlertState::Ok);
+        engine.evaluate(&snapshot(95.0), start + Duration::from_secs(4));
+        engine.evaluate(&snapshot(95.0), start + Duration::from_secs(12));
+        // The hold restarts from the second breach
+        assert!(engine.alerts[0].state == AlertState::Pending(start + Duration::from_secs(4)));
+    }
+
+    #[test]
+    fn missing_mount_is_reported() {
+        let rule = Rule {
+            name: String::from("data"),
+            condition: Condition::Mount {
+                mount: String::from("/data"),
+                above: 90.0,
+            },
+            for_secs: 0,
+            actions: Vec::new(),
+        };
+        let mut engine = AlertEngine::new(vec![rule], String::new());
+        let mut snapshot = Snapshot::default();
+        engine.evaluate(&snapshot, Instant::now());
+        assert!(engine.alerts[0].state == AlertState::Ok);
+        assert_eq!(
+            engine.alerts[0].error.as_deref(),
+            Some("mount /data not found or hidden by the [disks] filter")
+        );
+
+        // Bind mounts are folded into the disk they share a device with
+        snapshot.disks.push(DiskInfo {
+            mount_point: String::from("/srv"),
+            bind_mounts: vec![String::from("/data")],
+            total_space: 100,
+            used_space: 95,
+            ..DiskInfo::default()
+        });
+        engine.evaluate(&snapshot, Instant::now());
+        assert!(engine.alerts[0].error.is_none());
+        assert!(matches!(engine.alerts[0].state, AlertState::Firing(_)));
+        assert_eq!(engine.alerts[0].message, "mount /data usage 95.0% > 90%");
+    }
+}
@@ -0,0 +1,1051 @@
+use crate::config::Config;
+use crate::connections::{collect_connections, Connection, ConnectionKey, NET_ROOT};
+use crate::events::EventLog;
+use crate::inspect::{
+    read_fds, read_maps, read_process_sockets, read_rollup, FdInfo, MapInfo, MemoryRollup,
+};
+use crate::keys::{Action, Keymap};
+use crate::pinned::PinnedList;
+use crate::process_history::ProcessHistory;
+use crate::processes::{sort_processes, CpuMode, ProcessInfo, SortColumn, PROC_ROOT};
+use crate::remote::RemoteStatus;
+use crate::snapshot::Snapshot;
+use crate::sockets::Socket;
+use crate::system::{Pressure, PressureStats};
+use crate::theme::Theme;
+use crate::threads::{sort_threads, ThreadInfo, ThreadSampler, ThreadSort};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use std::collections::VecDeque;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+use sysinfo::Pid;
+use tui::layout::Rect;
+
+// Two clicks on the same row within this window count as a double-click
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+// Rows moved per scroll wheel notch
+const SCROLL_STEP: isize = 3;
+
+// Number of samples kept for history charts (~48s at the 400ms tick)
+pub const HISTORY_LEN: usize = 120;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tab {
+    Overview,
+    Processes,
+    Cpu,
+    Memory,
+    Disks,
+    Network,
+    Sensors,
+    Connections,
+    Events,
+}
+
+pub const TABS: [Tab; 9] = [
+    Tab::Overview,
+    Tab::Processes,
+    Tab::Cpu,
+    Tab::Memory,
+    Tab::Disks,
+    Tab::Network,
+    Tab::Sensors,
+    Tab::Connections,
+    Tab::Events,
+];
+
+impl Tab {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Tab::Overview => "Overview",
+            Tab::Processes => "Processes",
+            Tab::Cpu => "CPU",
+            Tab::Memory => "Memory",
+            Tab::Disks => "Disks",
+            Tab::Network => "Network",
+            Tab::Sensors => "Sensors",
+            Tab::Connections => "Connections",
+            Tab::Events => "Events",
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        TABS.iter().position(|t| t == self).unwrap_or(0)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ProcessView {
+    Processes,
+    Groups,
+}
+
+// avg10 of one resource's "some" and "full" lines, in hundredths of a percent
+pub struct PressureSeries {
+    pub some: VecDeque<u64>,
+    pub full: VecDeque<u64>,
+}
+
+impl PressureSeries {
+    fn new() -> PressureSeries {
+        PressureSeries {
+            some: VecDeque::with_capacity(HISTORY_LEN),
+            full: VecDeque::with_capacity(HISTORY_LEN),
+        }
+    }
+
+    fn record(&mut self, pressure: &Option<Pressure>) {
+        let (some, full) = match pressure {
+            Some(p) => (p.some.avg10, p.full.as_ref().map_or(0.0, |f| f.avg10)),
+            None => (0.0, 0.0),
+        };
+        push_history(&mut self.some, (some * 100.0) as u64);
+        push_history(&mut self.full, (full * 100.0) as u64);
+    }
+}
+
+pub struct PressureHistory {
+    pub cpu: PressureSeries,
+    pub memory: PressureSeries,
+    pub io: PressureSeries,
+}
+
+// CPU in hundredths of a percent, memory in bytes, network in bytes/s
+pub struct History {
+    pub cpu: VecDeque<u64>,
+    pub cores: Vec<VecDeque<u64>>,
+    pub memory: VecDeque<u64>,
+    pub swap: VecDeque<u64>,
+    pub rx: VecDeque<u64>,
+    pub tx: VecDeque<u64>,
+}
+
+// Screen areas recorded while drawing, used to hit-test mouse events.
+// Anything not on screen in the last frame stays an empty Rect.
+#[derive(Default)]
+pub struct Regions {
+    pub tabs: Vec<Rect>,
+    // Overview sections that open their full-screen tab when clicked
+    pub sections: Vec<(Rect, Tab)>,
+    pub process_header: Rect,
+    // x offset and width of each Processes column, in `App::columns` order
+    pub process_columns: Vec<(u16, u16)>,
+    pub process_rows: Rect,
+    pub pinned_rows: Rect,
+    pub detail: Rect,
+    pub detail_tabs: Vec<Rect>,
+    pub threads: Rect,
+    pub thread_rows: Rect,
+    pub help: Rect,
+    pub connection_rows: Rect,
+}
+
+pub fn contains(rect: Rect, column: u16, row: u16) -> bool {
+    column >= rect.x && column < rect.right() && row >= rect.y && row < rect.bottom()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DetailTab {
+    Info,
+    Files,
+    Sockets,
+    Maps,
+}
+
+pub const DETAIL_TABS: [DetailTab; 4] = [
+    DetailTab::Info,
+    DetailTab::Files,
+    DetailTab::Sockets,
+    DetailTab::Maps,
+];
+
+impl DetailTab {
+    pub fn title(&self) -> &'static str {
+        match self {
+            DetailTab::Info => "Info",
+            DetailTab::Files => "Files",
+            DetailTab::Sockets => "Sockets",
+            DetailTab::Maps => "Maps",
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        DETAIL_TABS.iter().position(|t| t == self).unwrap_or(0)
+    }
+}
+
+// Pairs left clicks into double-clicks for the app and the fleet view
+#[derive(Default)]
+pub struct Clicks {
+    last: Option<(Instant, u16, u16)>,
+}
+
+impl Clicks {
+    // Records a click; true when it completes a double-click
+    pub fn is_double(&mut self, column: u16, row: u16) -> bool {
+        let now = Instant::now();
+        let double = match self.last {
+            Some((at, c, r)) => r == row && c.abs_diff(column) <= 1 && at.elapsed() < DOUBLE_CLICK,
+            None => false,
+        };
+        // A double-click consumes the pair so a third click starts over
+        self.last = if double {
+            None
+        } else {
+            Some((now, column, row))
+        };
+        double
+    }
+}
+
+// A filter typed after `/`. Rows are kept when they contain the query,
+// ignoring case.
+#[derive(Default)]
+pub struct Search {
+    pub query: String,
+    pub editing: bool,
+}
+
+impl Search {
+    // Enter keeps the query and Esc drops it. Keys with Ctrl fall through
+    // so Ctrl-C still quits; false when not editing.
+    pub fn edit(&mut self, event: &KeyEvent) -> bool {
+        if !self.editing || event.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+        match event.code {
+            KeyCode::Enter => self.editing = false,
+            KeyCode::Esc => self.clear(),
+            KeyCode::Backspace => {
+                self.query.pop();
+            }
+            KeyCode::Char(c) => self.query.push(c),
+            _ => {}
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.editing = false;
+    }
+}
+
+// The detail overlay's Files, Sockets and Maps tabs. Only the active tab is
+// read, once when it opens and then every tick.
+pub struct Inspection {
+    pub tab: DetailTab,
+    pub files: Vec<FdInfo>,
+    pub sockets: Vec<Socket>,
+    pub maps: Vec<MapInfo>,
+    pub rollup: Option<MemoryRollup>,
+    // Usually "Permission denied" for other users' processes
+    pub error: Option<String>,
+    pub scroll: usize,
+    pub search: Search,
+}
+
+impl Inspection {
+    fn new() -> Inspection {
+        Inspection {
+            tab: DetailTab::Info,
+            files: Vec::new(),
+            sockets: Vec::new(),
+            maps: Vec::new(),
+            rollup: None,
+            error: None,
+            scroll: 0,
+            search: Search::default(),
+        }
+    }
+
+    fn refresh(&mut self, pid: Pid) {
+        let root = Path::new(PROC_ROOT);
+        let pid = pid.as_u32();
+        let result = match self.tab {
+            DetailTab::Info => {
+                self.rollup = read_rollup(pid, root);
+                Ok(())
+            }
+            DetailTab::Files => read_fds(pid, root).map(|files| self.files = files),
+            DetailTab::Sockets => {
+                read_process_sockets(pid, root).map(|sockets| self.sockets = sockets)
+            }
+            DetailTab::Maps => read_maps(pid, root).map(|maps| {
+                self.maps = maps;
+                self.rollup = read_rollup(pid, root);
+            }),
+        };
+        self.error = result.err().map(|e| e.to_string());
+    }
+
+    // A new tab starts at the top with no filter
+    fn select(&mut self, tab: DetailTab) {
+        self.tab = tab;
+        self.scroll = 0;
+        self.search.clear();
+    }
+
+    fn scroll_by(&mut self, delta: isize) {
+        // The draw clamps the upper end once it knows the row count
+        self.scroll = (self.scroll as isize + delta).max(0) as usize;
+    }
+}
+
+// The Connections tab, only collected while it is showing since it reads
+// every process's fds
+#[derive(Default)]
+pub struct ConnectionsView {
+    pub connections: Vec<Connection>,
+    // Follows the socket so it survives refreshes
+    pub selected: Option<ConnectionKey>,
+    pub scroll: usize,
+    pub search: Search,
+}
+
+impl ConnectionsView {
+    fn refresh(&mut self, processes: &[ProcessInfo]) {
+        self.connections =
+            collect_connections(processes, Path::new(NET_ROOT), Path::new(PROC_ROOT));
+    }
+
+    // The rows left after the search
+    pub fn visible(&self) -> Vec<&Connection> {
+        self.connections
+            .iter()
+            .filter(|c| c.matches(&self.search.query))
+            .collect()
+    }
+
+    pub fn selected_index(&self, visible: &[&Connection]) -> Option<usize> {
+        let key = self.selected.as_ref()?;
+        visible.iter().position(|c| c.key() == *key)
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let visible = self.visible();
+        if visible.is_empty() {
+            self.selected = None;
+            return;
+        }
+        let last = visible.len() as isize - 1;
+        let index = match self.selected_index(&visible) {
+            Some(index) => (index as isize + delta).clamp(0, last),
+            None if delta < 0 => last,
+            None => 0,
+        };
+        self.selected = Some(visible[index as usize].key());
+    }
+
+    fn selected_pid(&self) -> Option<Pid> {
+        let key = self.selected.as_ref()?;
+        self.connections.iter().find(|c| c.key() == *key)?.pid
+    }
+}
+
+// The thread overlay for one process, refreshed every tick
+pub struct ThreadView {
+    pub sampler: ThreadSampler,
+    pub threads: Vec<ThreadInfo>,
+    // Follows the TID, like the process selection follows the PID
+    pub selected_tid: Option<u32>,
+    pub scroll: usize,
+    pub sort: ThreadSort,
+    pub descending: bool,
+}
+
+impl ThreadView {
+    fn refresh(&mut self, now: Instant) {
+        self.threads = self.sampler.sample(Path::new(PROC_ROOT), now);
+        sort_threads(&mut self.threads, self.sort, self.descending);
+    }
+
+    pub fn selected_index(&self) -> Option<usize> {
+        let tid = self.selected_tid?;
+        self.threads.iter().position(|t| t.tid == tid)
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.threads.is_empty() {
+            self.selected_tid = None;
+            return;
+        }
+        let last = self.threads.len() as isize - 1;
+        let index = match self.selected_index() {
+            Some(index) => (index as isize + delta).clamp(0, last),
+            None if delta < 0 => last,
+            None => 0,
+        };
+        self.selected_tid = Some(self.threads[index as usize].tid);
+    }
+
+    // Same rules as the process table: resource columns start largest-first
+    fn set_sort(&mut self, sort: ThreadSort) {
+        if self.sort == sort {
+            self.descending = !self.descending;
+        } else {
+            self.sort = sort;
+            self.descending = sort == ThreadSort::Cpu;
+        }
+        sort_threads(&mut self.threads, self.sort, self.descending);
+    }
+}
+
+pub struct App {
+    pub tab: Tab,
+    pub process_view: ProcessView,
+    pub history: History,
+    pub pressure_history: PressureHistory,
+    pub pinned: PinnedList,
+    // Selection follows the PID so it survives re-sorting between refreshes
+    pub selected_pid: Option<Pid>,
+    pub process_scroll: usize,
+    // Shown columns, from the config
+    pub columns: Vec<SortColumn>,
+    pub sort_column: SortColumn,
+    pub sort_descending: bool,
+    pub cpu_mode: CpuMode,
+    // Logical CPUs as of the last refresh, which Solaris mode divides by
+    pub cpu_count: usize,
+    // PID shown in the process detail overlay
+    pub detail_pid: Option<Pid>,
+    pub inspection: Inspection,
+    pub thread_view: Option<ThreadView>,
+    pub connections: ConnectionsView,
+    // The CPU tab shows the socket/core topology instead of the core grid
+    pub show_topology: bool,
+    pub topology_scroll: usize,
+    pub events: EventLog,
+    pub events_scroll: usize,
+    pub process_history: ProcessHistory,
+    // Set while showing an agent's snapshots, which turns off the views
+    // that read this machine's /proc directly
+    pub remote: Option<RemoteStatus>,
+    // Opened from the fleet view, which Esc returns to
+    pub in_fleet: bool,
+    pub mouse_capture: bool,
+    pub show_help: bool,
+    pub keymap: Keymap,
+    pub regions: Regions,
+    clicks: Clicks,
+    pub theme: Theme,
+}
+
+pub fn push_history(history: &mut VecDeque<u64>, value: u64) {
+    if history.len() == HISTORY_LEN {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+impl App {
+    pub fn new(
+        config: &Config,
+        theme: Theme,
+        keymap: Keymap,
+        columns: Vec<SortColumn>,
+        history_len: usize,
+    ) -> App {
+        App {
+            tab: Tab::Overview,
+            process_view: ProcessView::Processes,
+            history: History {
+                cpu: VecDeque::with_capacity(HISTORY_LEN),
+                cores: Vec::new(),
+                memory: VecDeque::with_capacity(HISTORY_LEN),
+                swap: VecDeque::with_capacity(HISTORY_LEN),
+                rx: VecDeque::with_capacity(HISTORY_LEN),
+                tx: VecDeque::with_capacity(HISTORY_LEN),
+            },
+            pressure_history: PressureHistory {
+                cpu: PressureSeries::new(),
+                memory: PressureSeries::new(),
+                io: PressureSeries::new(),
+            },
+            pinned: PinnedList::new(config.pinned.clone()),
+            selected_pid: None,
+            process_scroll: 0,
+            columns,
+            sort_column: SortColumn::Pid,
+            sort_descending: false,
+            cpu_mode: config.processes.cpu_mode,
+            cpu_count: 1,
+            detail_pid: None,
+            inspection: Inspection::new(),
+            thread_view: None,
+            connections: ConnectionsView::default(),
+            show_topology: false,
+            topology_scroll: 0,
+            events: EventLog::new(config.events.clone()),
+            events_scroll: 0,
+            process_history: ProcessHistory::new(history_len),
+            remote: None,
+            in_fleet: false,
+            mouse_capture: true,
+            show_help: false,
+            keymap,
+            regions: Regions::default(),
+            clicks: Clicks::default(),
+            theme,
+        }
+    }
+
+    pub fn selected_index(&self, processes: &[ProcessInfo]) -> Option<usize> {
+        let pid = self.selected_pid?;
+        processes.iter().position(|p| p.pid == pid)
+    }
+
+    // Moves the selection by `delta` rows, clamped to the table
+    pub fn move_selection(&mut self, processes: &[ProcessInfo], delta: isize) {
+        if processes.is_empty() {
+            self.selected_pid = None;
+            return;
+        }
+        let index = match self.selected_index(processes) {
+            Some(index) => (index as isize + delta).clamp(0, processes.len() as isize - 1),
+            None if delta < 0 => processes.len() as isize - 1,
+            None => 0,
+        };
+        self.selected_pid = Some(processes[index as usize].pid);
+    }
+
+    pub fn toggle_pin(&mut self, processes: &[ProcessInfo], now: Instant) {
+        if let Some(index) = self.selected_index(processes) {
+            let (cpu_mode, cpus) = (self.cpu_mode, self.cpu_count);
+            self.pinned
+                .toggle(&processes[index], processes, cpu_mode, cpus, now);
+        }
+    }
+
+    pub fn record(&mut self, snapshot: &Snapshot, now: Instant) {
+        let stats = &snapshot.stats;
+        self.cpu_count = stats.cpu_cores.len().max(1);
+        let history = &mut self.history;
+        push_history(&mut history.cpu, (stats.cpu_global_usage * 100.0) as u64);
+        history
+            .cores
+            .resize_with(stats.cpu_cores.len(), VecDeque::new);
+        for (core, usage) in history.cores.iter_mut().zip(&stats.cpu_cores) {
+            push_history(core, (usage * 100.0) as u64);
+        }
+        push_history(&mut history.memory, stats.used_memory);
+        push_history(&mut history.swap, stats.used_swap);
+        push_history(
+            &mut history.rx,
+            snapshot.networks.iter().map(|n| n.rx_rate).sum(),
+        );
+        push_history(
+            &mut history.tx,
+            snapshot.networks.iter().map(|n| n.tx_rate).sum(),
+        );
+
+        self.record_pressure(&snapshot.pressure);
+        let (processes, cpu_mode, cpus) = (&snapshot.processes, snapshot.cpu_mode, self.cpu_count);
+        self.pinned.update(processes, cpu_mode, cpus, now);
+        self.events.update(processes, SystemTime::now());
+        self.process_history.update(processes, cpu_mode, cpus, now);
+        if let Some(view) = &mut self.thread_view {
+            view.refresh(now);
+        }
+        if let Some(pid) = self.detail_pid {
+            self.refresh_inspection(pid);
+        }
+        if self.tab == Tab::Connections && self.remote.is_none() {
+            self.connections.refresh(&snapshot.processes);
+        }
+    }
+
+    fn record_pressure(&mut self, pressure: &PressureStats) {
+        let history = &mut self.pressure_history;
+        history.cpu.record(&pressure.cpu);
+        history.memory.record(&pressure.memory);
+        history.io.record(&pressure.io);
+    }
+
+    // Selects the connection's owner on the Processes tab
+    fn jump_to_owner(&mut self) {
+        if let Some(pid) = self.connections.selected_pid() {
+            self.selected_pid = Some(pid);
+            self.process_view = ProcessView::Processes;
+            self.tab = Tab::Processes;
+        }
+    }
+
+    pub fn select_tab(&mut self, index: usize) {
+        if let Some(tab) = TABS.get(index) {
+            self.tab = *tab;
+        }
+    }
+
+    pub fn next_tab(&mut self) {
+        self.tab = TABS[(self.tab.index() + 1) % TABS.len()];
+    }
+
+    pub fn previous_tab(&mut self) {
+        self.tab = TABS[(self.tab.index() + TABS.len() - 1) % TABS.len()];
+    }
+
+    pub fn toggle_groups(&mut self) {
+        self.process_view = match self.process_view {
+            ProcessView::Processes => ProcessView::Groups,
+            ProcessView::Groups => ProcessView::Processes,
+        };
+    }
+
+    pub fn sort(&self, processes: &mut [ProcessInfo]) {
+        sort_processes(processes, self.sort_column, self.sort_descending);
+    }
+
+    // The collector uses the new mode from the next refresh; the snapshot
+    // already shown is rescaled so the tables switch straight away
+    fn toggle_cpu_mode(&mut self, snapshot: &mut Snapshot) {
+        self.cpu_mode = self.cpu_mode.toggled();
+        snapshot.set_cpu_mode(self.cpu_mode);
+    }
+
+    // Clicking the sorted column again flips the direction
+    pub fn set_sort(&mut self, column: SortColumn, processes: &mut [ProcessInfo]) {
+        if self.sort_column == column {
+            self.sort_descending = !self.sort_descending;
+        } else {
+            self.sort_column = column;
+            self.sort_descending = column.descending_by_default();
+        }
+        self.sort(processes);
+    }
+
+    pub fn open_detail(&mut self, processes: &[ProcessInfo]) {
+        if let Some(index) = self.selected_index(processes) {
+            self.show_detail(processes[index].pid);
+        }
+    }
+
+    // Every opening starts on the Info tab
+    fn show_detail(&mut self, pid: Pid) {
+        self.detail_pid = Some(pid);
+        self.inspection = Inspection::new();
+        self.refresh_inspection(pid);
+    }
+
+    // Files, sockets and maps come from this machine's /proc
+    fn refresh_inspection(&mut self, pid: Pid) {
+        if self.remote.is_some() {
+            self.inspection.error = Some(String::from("Not available for a remote host"));
+        } else {
+            self.inspection.refresh(pid);
+        }
+    }
+
+    pub fn close_detail(&mut self) {
+        self.detail_pid = None;
+    }
+
+    fn select_detail_tab(&mut self, index: usize) {
+        if let (Some(pid), Some(tab)) = (self.detail_pid, DETAIL_TABS.get(index)) {
+            self.inspection.select(*tab);
+            self.refresh_inspection(pid);
+        }
+    }
+
+    pub fn start_search(&mut self) {
+        if self.detail_pid.is_some() {
+            if self.inspection.tab != DetailTab::Info {
+                self.inspection.search.editing = true;
+            }
+        } else if self.tab == Tab::Connections {
+            self.connections.search.editing = true;
+        }
+    }
+
+    // Takes the key while a search query is being typed
+    pub fn edit_search(&mut self, event: &KeyEvent) -> bool {
+        if self.detail_pid.is_some() {
+            let consumed = self.inspection.search.edit(event);
+            if consumed {
+                self.inspection.scroll = 0;
+            }
+            consumed
+        } else if self.tab == Tab::Connections {
+            self.connections.search.edit(event)
+        } else {
+            false
+        }
+    }
+
+    // Opens on the process in the detail overlay, else the selected one
+    pub fn open_threads(&mut self, processes: &[ProcessInfo]) {
+        // Threads are sampled from this machine's /proc
+        if self.remote.is_some() {
+            return;
+        }
+        let pid = match self.detail_pid {
+            Some(pid) => pid,
+            None => match self.selected_index(processes) {
+                Some(index) => processes[index].pid,
+                None => return,
+            },
+        };
+        let mut view = ThreadView {
+            sampler: ThreadSampler::new(pid.as_u32()),
+            threads: Vec::new(),
+            selected_tid: None,
+            scroll: 0,
+            sort: ThreadSort::Cpu,
+            descending: true,
+        };
+        view.refresh(Instant::now());
+        self.thread_view = Some(view);
+    }
+
+    pub fn handle_mouse(&mut self, event: MouseEvent, processes: &mut [ProcessInfo]) {
+        let (column, row) = (event.column, event.row);
+        // Help is modal: only a click outside it does anything, closing it
+        if self.show_help {
+            if event.kind == MouseEventKind::Down(MouseButton::Left)
+                && !contains(self.regions.help, column, row)
+            {
+                self.show_help = false;
+            }
+            return;
+        }
+        if let Some(view) = &mut self.thread_view {
+            match event.kind {
+                MouseEventKind::ScrollUp => return view.move_selection(-SCROLL_STEP),
+                MouseEventKind::ScrollDown => return view.move_selection(SCROLL_STEP),
+                _ => {}
+            }
+        }
+        if self.detail_pid.is_some() {
+            match event.kind {
+                MouseEventKind::ScrollUp => return self.inspection.scroll_by(-SCROLL_STEP),
+                MouseEventKind::ScrollDown => return self.inspection.scroll_by(SCROLL_STEP),
+                _ => {}
+            }
+        }
+        let previous = self.tab;
+        match event.kind {
+            MouseEventKind::ScrollUp if self.tab == Tab::Cpu && self.show_topology => {
+                self.scroll_topology(-SCROLL_STEP)
+            }
+            MouseEventKind::ScrollDown if self.tab == Tab::Cpu && self.show_topology => {
+                self.scroll_topology(SCROLL_STEP)
+            }
+            MouseEventKind::ScrollUp if self.tab == Tab::Events => self.scroll_events(-SCROLL_STEP),
+            MouseEventKind::ScrollDown if self.tab == Tab::Events => {
+                self.scroll_events(SCROLL_STEP)
+            }
+            MouseEventKind::ScrollUp if self.tab == Tab::Connections => {
+                self.connections.move_selection(-SCROLL_STEP)
+            }
+            MouseEventKind::ScrollDown if self.tab == Tab::Connections => {
+                self.connections.move_selection(SCROLL_STEP)
+            }
+            MouseEventKind::ScrollUp if self.detail_pid.is_none() => {
+                self.move_selection(processes, -SCROLL_STEP)
+            }
+            MouseEventKind::ScrollDown if self.detail_pid.is_none() => {
+                self.move_selection(processes, SCROLL_STEP)
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                let double = self.clicks.is_double(column, row);
+                self.click(column, row, double, processes);
+            }
+            _ => {}
+        }
+        self.entered_tab(previous, processes);
+    }
+
+    // Connections are only collected while the tab shows, so fill it in
+    // right away rather than on the next tick
+    fn entered_tab(&mut self, previous: Tab, processes: &[ProcessInfo]) {
+        if self.tab == Tab::Connections && previous != Tab::Connections && self.remote.is_none() {
+            self.connections.refresh(processes);
+        }
+    }
+
+    fn click(&mut self, column: u16, row: u16, double: bool, processes: &mut [ProcessInfo]) {
+        let regions = &self.regions;
+        // Overlays are modal: clicking outside one closes it
+        if let Some(view) = &mut self.thread_view {
+            if !contains(regions.threads, column, row) {
+                self.thread_view = None;
+            } else if contains(regions.thread_rows, column, row) {
+                let index = view.scroll + (row - regions.thread_rows.y) as usize;
+                if let Some(thread) = view.threads.get(index) {
+                    view.selected_tid = Some(thread.tid);
+                }
+            }
+            return;
+        }
+        if self.detail_pid.is_some() {
+            if !contains(regions.detail, column, row) {
+                self.close_detail();
+            } else if let Some(index) = regions
+                .detail_tabs
+                .iter()
+                .position(|r| contains(*r, column, row))
+            {
+                self.select_detail_tab(index);
+            }
+            return;
+        }
+
+        if let Some(index) = regions.tabs.iter().position(|r| contains(*r, column, row)) {
+            self.select_tab(index);
+        } else if let Some((_, tab)) = regions
+            .sections
+            .iter()
+            .find(|(r, _)| contains(*r, column, row))
+        {
+            self.tab = *tab;
+        } else if contains(regions.process_header, column, row) {
+            let clicked = regions
+                .process_columns
+                .iter()
+                .position(|(x, width)| column >= *x && column < x + width);
+            if let Some(index) = clicked {
+                self.set_sort(self.columns[index], processes);
+            }
+        } else if contains(regions.process_rows, column, row) {
+            let index = self.process_scroll + (row - regions.process_rows.y) as usize;
+            if let Some(p) = processes.get(index) {
+                self.selected_pid = Some(p.pid);
+                if double {
+                    self.show_detail(p.pid);
+                }
+            }
+        } else if contains(regions.connection_rows, column, row) {
+            let index = self.connections.scroll + (row - regions.connection_rows.y) as usize;
+            if let Some(c) = self.connections.visible().get(index) {
+                self.connections.selected = Some(c.key());
+                if double {
+                    self.jump_to_owner();
+                }
+            }
+        } else if contains(regions.pinned_rows, column, row) {
+            let index = (row - regions.pinned_rows.y) as usize;
+            if let Some(pid) = self.pinned.entries.get(index).and_then(|e| e.pid) {
+                self.selected_pid = Some(pid);
+                if double {
+                    self.show_detail(pid);
+                }
+            }
+        }
+    }
+
+    fn toggle_topology(&mut self) {
+        self.show_topology = !self.show_topology;
+        self.topology_scroll = 0;
+    }
+
+    fn scroll_topology(&mut self, delta: isize) {
+        // The draw clamps the upper end once it knows the row count
+        self.topology_scroll = (self.topology_scroll as isize + delta).max(0) as usize;
+    }
+
+    fn scroll_events(&mut self, delta: isize) {
+        // The draw clamps the upper end once it knows the row count
+        self.events_scroll = (self.events_scroll as isize + delta).max(0) as usize;
+    }
+
+    // Closes the topmost overlay; false when there was nothing to close
+    pub fn back(&mut self) -> bool {
+        if self.show_help {
+            self.show_help = false;
+        } else if self.thread_view.is_some() {
+            self.thread_view = None;
+        } else if self.detail_pid.is_some() && !self.inspection.search.query.is_empty() {
+            self.inspection.search.clear();
+        } else if self.detail_pid.is_some() {
+            self.close_detail();
+        } else if self.tab == Tab::Connections && !self.connections.search.query.is_empty() {
+            self.connections.search.clear();
+        } else if self.tab == Tab::Cpu && self.show_topology {
+            self.show_topology = false;
+        } else {
+            return false;
+        }
+        true
+    }
+
+    // Runs everything except Quit, Back, ToggleMouse and Suspend, which need
+    // the terminal and are handled by the main loop
+    pub fn apply(&mut self, action: Action, snapshot: &mut Snapshot) {
+        // Help is modal: everything but closing it is ignored
+        if self.show_help {
+            if action == Action::Help {
+                self.show_help = false;
+            }
+            return;
+        }
+        if action == Action::ToggleCpuMode {
+            return self.toggle_cpu_mode(snapshot);
+        }
+        let processes = &mut snapshot.processes;
+        // Navigation and sorting go to the thread overlay while it is open
+        if let Some(view) = &mut self.thread_view {
+            match action {
+                Action::Up => return view.move_selection(-1),
+                Action::Down => return view.move_selection(1),
+                Action::PageUp => return view.move_selection(-20),
+                Action::PageDown => return view.move_selection(20),
+                Action::Home => return view.move_selection(isize::MIN / 2),
+                Action::End => return view.move_selection(isize::MAX / 2),
+                Action::SortNext => return view.set_sort(view.sort.next()),
+                Action::SortReverse => return view.set_sort(view.sort),
+                _ => {}
+            }
+        }
+        // and to the detail overlay's tabs while that is open
+        if self.detail_pid.is_some() {
+            let tab = self.inspection.tab.index();
+            let count = DETAIL_TABS.len();
+            match action {
+                Action::NextTab => return self.select_detail_tab((tab + 1) % count),
+                Action::PreviousTab => return self.select_detail_tab((tab + count - 1) % count),
+                Action::Up => return self.inspection.scroll_by(-1),
+                Action::Down => return self.inspection.scroll_by(1),
+                Action::PageUp => return self.inspection.scroll_by(-20),
+                Action::PageDown => return self.inspection.scroll_by(20),
+                Action::Home => return self.inspection.scroll_by(isize::MIN / 2),
+                Action::End => return self.inspection.scroll_by(isize::MAX / 2),
+                _ => {}
+            }
+        }
+        // and to the connection list on its tab
+        if self.tab == Tab::Connections {
+            let view = &mut self.connections;
+            match action {
+                Action::Up => return view.move_selection(-1),
+                Action::Down => return view.move_selection(1),
+                Action::PageUp => return view.move_selection(-20),
+                Action::PageDown => return view.move_selection(20),
+                Action::Home => return view.move_selection(isize::MIN / 2),
+                Action::End => return view.move_selection(isize::MAX / 2),
+                Action::Details => return self.jump_to_owner(),
+                _ => {}
+            }
+        }
+        // and to the event list on its tab
+        if self.tab == Tab::Events {
+            match action {
+                Action::Up => return self.scroll_events(-1),
+                Action::Down => return self.scroll_events(1),
+                Action::PageUp => return self.scroll_events(-20),
+                Action::PageDown => return self.scroll_events(20),
+                Action::Home => return self.scroll_events(isize::MIN / 2),
+                Action::End => return self.scroll_events(isize::MAX / 2),
+                _ => {}
+            }
+        }
+        // and to the topology list on the CPU tab
+        if self.tab == Tab::Cpu {
+            match action {
+                Action::Details => return self.toggle_topology(),
+                Action::Up if self.show_topology => return self.scroll_topology(-1),
+                Action::Down if self.show_topology => return self.scroll_topology(1),
+                Action::PageUp if self.show_topology => return self.scroll_topology(-20),
+                Action::PageDown if self.show_topology => return self.scroll_topology(20),
+                Action::Home if self.show_topology => return self.scroll_topology(isize::MIN / 2),
+                Action::End if self.show_topology => return self.scroll_topology(isize::MAX / 2),
+                _ => {}
+            }
+        }
+        let previous = self.tab;
+        match action {
+            Action::Help => self.show_help = !self.show_help,
+            Action::Details => self.open_detail(processes),
+            Action::Threads => self.open_threads(processes),
+            Action::Search => self.start_search(),
+            Action::ToggleGroups => self.toggle_groups(),
+            Action::TogglePin => self.toggle_pin(processes, Instant::now()),
+            Action::SortNext => {
+                // A sort column that is not shown restarts at the first one
+                let next = match self.columns.iter().position(|c| *c == self.sort_column) {
+                    Some(index) => (index + 1) % self.columns.len(),
+                    None => 0,
+                };
+                if let Some(column) = self.columns.get(next) {
+                    self.set_sort(*column, processes);
+                }
+            }
+            Action::SortReverse => self.set_sort(self.sort_column, processes),
+            Action::NextTab => self.next_tab(),
+            Action::PreviousTab => self.previous_tab(),
+            Action::SelectTab(index) => self.select_tab(index),
+            Action::Up => self.move_selection(processes, -1),
+            Action::Down => self.move_selection(processes, 1),
+            Action::PageUp => self.move_selection(processes, -20),
+            Action::PageDown => self.move_selection(processes, 20),
+            Action::Home => self.move_selection(processes, isize::MIN / 2),
+            Action::End => self.move_selection(processes, isize::MAX / 2),
+            Action::Quit
+            | Action::Back
+            | Action::ToggleMouse
+            | Action::Suspend
+            | Action::ToggleCpuMode => {}
+        }
+        self.entered_tab(previous, processes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sockets::Socket;
+
+    fn connection(remote: &str, state: &'static str, inode: u64, pid: Option<usize>) -> Connection {
+        Connection {
+            socket: Socket {
+                protocol: "tcp",
+                local: String::from("10.0.0.1:443"),
+                remote: remote.to_string(),
+                state,
+                inode,
+            },
+            pid: pid.map(Pid::from),
+            process: String::new(),
+        }
+    }
+
+    #[test]
+    fn clicks_pair_into_double_clicks() {
+        let mut clicks = Clicks::default();
+        assert!(!clicks.is_double(10, 5));
+        // A column off still counts; the pair is then used up
+        assert!(clicks.is_double(11, 5));
+        assert!(!clicks.is_double(11, 5));
+        assert!(!clicks.is_double(11, 6));
+        assert!(!clicks.is_double(20, 6));
+    }
+
+    #[test]
+    fn connection_selection_tells_inode_zero_rows_apart() {
+        let mut view = ConnectionsView {
+            connections: vec![
+                connection("0.0.0.0:0", "LISTEN", 7, Some(100)),
+                connection("10.0.0.2:50000", "TIME_WAIT", 0, None),
+                connection("10.0.0.3:50001", "TIME_WAIT", 0, None),
+                connection("10.0.0.4:50002", "ESTABLISHED", 8, Some(200)),
+            ],
+            ..ConnectionsView::default()
+        };
+        view.move_selection(1);
+        view.move_selection(1);
+        view.move_selection(1);
+        let visible = view.visible();
+        assert_eq!(view.selected_index(&visible), Some(2));
+        assert_eq!(visible[2].socket.remote, "10.0.0.3:50001");
+        assert_eq!(view.selected_pid(), None);
+
+        view.move_selection(1);
+        assert_eq!(view.selected_pid(), Some(Pid::from(200)));
+
+        // The selection follows its row when a refresh reorders the list
+        view.move_selection(-1);
+        view.connections.swap(1, 2);
+        let visible = view.visible();
+        assert_eq!(view.selected_index(&visible), Some(1));
+    }
+}
@@ -0,0 +1,339 @@
+use crate::processes::ProcessInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+pub const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CgroupInfo {
+    pub path: String,
+    pub container_id: Option<String>,
+    pub runtime: Option<String>,
+    pub unit: Option<String>,
+}
+
+impl CgroupInfo {
+    pub fn from_path(path: &str) -> CgroupInfo {
+        let (runtime, container_id) = match parse_container(path) {
+            Some((runtime, id)) => (Some(runtime), Some(id)),
+            None => (None, None),
+        };
+        CgroupInfo {
+            path: path.to_string(),
+            container_id,
+            runtime,
+            unit: parse_systemd_unit(path),
+        }
+    }
+
+    // Short label for table cells: container first, then unit, then raw path
+    pub fn label(&self) -> String {
+        if let Some(id) = &self.container_id {
+            let short: String = id.chars().take(12).collect();
+            return match &self.runtime {
+                Some(runtime) => format!("{}:{}", runtime, short),
+                None => short,
+            };
+        }
+        if let Some(unit) = &self.unit {
+            return unit.clone();
+        }
+        self.path.clone()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CgroupLimits {
+    pub memory_max: Option<u64>,
+    pub memory_current: Option<u64>,
+    // CPU quota as a percentage of one core, from cpu.max
+    pub cpu_max: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CgroupGroup {
+    pub info: CgroupInfo,
+    pub num_processes: usize,
+    pub cpu: f32,
+    pub memory: u64,
+    pub read_bytes: u64,
+    pub written_bytes: u64,
+    pub limits: CgroupLimits,
+}
+
+pub fn read_process_cgroup(pid: u32, root: &Path) -> Option<CgroupInfo> {
+    let contents = fs::read_to_string(root.join(pid.to_string()).join("cgroup")).ok()?;
+    let path = parse_proc_cgroup(&contents)?;
+    Some(CgroupInfo::from_path(&path))
+}
+
+// Picks the unified (v2) hierarchy entry if present, otherwise the systemd
+// named hierarchy, otherwise whatever v1 controller comes first.
+pub fn parse_proc_cgroup(contents: &str) -> Option<String> {
+    let mut fallback = None;
+    let mut systemd = None;
+    let mut unified_root = false;
+    for line in contents.lines() {
+        let mut fields = line.splitn(3, ':');
+        let (id, controllers, path) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(id), Some(controllers), Some(path)) => (id, controllers, path),
+            _ => continue,
+        };
+        if id == "0" && controllers.is_empty() {
+            // "0::/" on hybrid hosts just means no v2 group was assigned
+            if path != "/" {
+                return Some(path.to_string());
+            }
+            unified_root = true;
+            continue;
+        }
+        if controllers == "name=systemd" {
+            systemd = Some(path.to_string());
+        } else if fallback.is_none() {
+            fallback = Some(path.to_string());
+        }
+    }
+    systemd
+        .or(fallback)
+        .or_else(|| unified_root.then(|| "/".to_string()))
+}
+
+fn is_container_id(s: &str) -> bool {
+    s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn parse_container(path: &str) -> Option<(String, String)> {
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+    for (i, component) in components.iter().enumerate().rev() {
+        let trimmed = component.trim_end_matches(".scope");
+        for (prefix, runtime) in [
+            ("docker-", "docker"),
+            ("libpod-", "podman"),
+            ("cri-containerd-", "containerd"),
+            ("crio-", "crio"),
+        ] {
+            if let Some(id) = trimmed.strip_prefix(prefix) {
+                if is_container_id(id) {
+                    return Some((runtime.to_string(), id.to_string()));
+                }
+            }
+        }
+        if is_container_id(trimmed) {
+            // cgroup v1 style: /docker/<id>, /kubepods/burstable/pod.../<id>
+            let runtime = components[..i]
+                .iter()
+                .find_map(|c| match *c {
+                    "docker" => Some("docker"),
+                    "libpod_parent" | "machine.slice" => Some("podman"),
+                    c if c.starts_with("kubepods") => Some("k8s"),
+                    _ => None,
+                })
+                .unwrap_or("container");
+            return Some((runtime.to_string(), trimmed.to_string()));
+        }
+    }
+    None
+}
+
+fn parse_systemd_unit(path: &str) -> Option<String> {
+    path.split('/')
+        .rev()
+        .find(|c| {
+            [".service", ".scope", ".socket", ".mount", ".swap"]
+                .iter()
+                .any(|suffix| c.ends_with(suffix))
+        })
+        .map(|c| c.to_string())
+}
+
+fn read_value(dir: &Path, file: &str) -> Option<String> {
+    fs::read_to_string(dir.join(file))
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+// Limits are only exposed by the unified hierarchy; on v1 hosts the files are
+// missing and every field stays `None`.
+pub fn read_cgroup_limits(root: &Path, path: &str) -> CgroupLimits {
+    let dir = root.join(path.trim_start_matches('/'));
+    let memory_max = read_value(&dir, "memory.max").and_then(|v| v.parse::<u64>().ok());
+    let memory_current = read_value(&dir, "memory.current").and_then(|v| v.parse::<u64>().ok());
+    let cpu_max = read_value(&dir, "cpu.max").and_then(|v| {
+        let mut fields = v.split_whitespace();
+        let quota = fields.next()?.parse::<f32>().ok()?;
+        let period = fields.next()?.parse::<f32>().ok()?;
+        if period > 0.0 {
+            Some(quota / period * 100.0)
+        } else {
+            None
+        }
+    });
+
+    CgroupLimits {
+        memory_max,
+        memory_current,
+        cpu_max,
+    }
+}
+
+pub fn collect_cgroup_groups(processes: &[ProcessInfo], root: &Path) -> Vec<CgroupGroup> {
+    let mut groups: HashMap<String, CgroupGroup> = HashMap::new();
+    for p in processes {
+        // Userland threads share their leader's memory and I/O counters
+        if p.is_thread {
+            continue;
+        }
+        let info = match &p.cgroup {
+            Some(info) => info,
+            None => continue,
+        };
+        let group = groups
+            .entry(info.path.clone())
+            .or_insert_with(|| CgroupGroup {
+                info: info.clone(),
+                num_processes: 0,
+                cpu: 0.0,
+                memory: 0,
+                read_bytes: 0,
+                written_bytes: 0,
+                limits: read_cgroup_limits(root, &info.path),
+            });
+        group.num_processes += 1;
+        group.cpu += p.cpu;
+        group.memory += p.memory;
+        group.read_bytes += p.read_bytes;
+        group.written_bytes += p.written_bytes;
+    }
+
+    let mut groups: Vec<CgroupGroup> = groups.into_values().collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.memory));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    const DOCKER_ID: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn unified_hierarchy_wins() {
+        let contents = "12:memory:/legacy\n1:name=systemd:/system.slice/cron.service\n\
+                        0::/user.slice/user-1000.slice/session-2.scope\n";
+        assert_eq!(
+            parse_proc_cgroup(contents).as_deref(),
+            Some("/user.slice/user-1000.slice/session-2.scope")
+        );
+    }
+
+    #[test]
+    fn unified_root_falls_back_to_v1() {
+        // Hybrid hosts report "0::/" for processes only placed in v1 groups
+        let contents = "0::/\n4:cpu,cpuacct:/cpu-group\n1:name=systemd:/system.slice/ssh.service\n";
+        assert_eq!(
+            parse_proc_cgroup(contents).as_deref(),
+            Some("/system.slice/ssh.service")
+        );
+        let contents = "0::/\n4:cpu,cpuacct:/cpu-group\n3:memory:/memory-group\n";
+        assert_eq!(parse_proc_cgroup(contents).as_deref(), Some("/cpu-group"));
+        // A v2-only process in the root group
+        assert_eq!(parse_proc_cgroup("0::/\n").as_deref(), Some("/"));
+        assert_eq!(parse_proc_cgroup(""), None);
+    }
+
+    #[test]
+    fn systemd_scoped_containers() {
+        let container = |path: String| parse_container(&path);
+        assert_eq!(
+            container(format!("/system.slice/docker-{}.scope", DOCKER_ID)),
+            Some(("docker".to_string(), DOCKER_ID.to_string()))
+        );
+        assert_eq!(
+            container(format!(
+                "/machine.slice/libpod-{}.scope/container",
+                DOCKER_ID
+            )),
+            Some(("podman".to_string(), DOCKER_ID.to_string()))
+        );
+        assert_eq!(
+            container(format!(
+                "/kubepods.slice/kubepods-besteffort.slice/cri-containerd-{}.scope",
+                DOCKER_ID
+            )),
+            Some(("containerd".to_string(), DOCKER_ID.to_string()))
+        );
+        assert_eq!(
+            container(format!("/kubepods.slice/crio-{}.scope", DOCKER_ID)),
+            Some(("crio".to_string(), DOCKER_ID.to_string()))
+        );
+    }
+
+    #[test]
+    fn cgroupfs_containers() {
+        let container = |path: String| parse_container(&path);
+        assert_eq!(
+            container(format!("/docker/{}", DOCKER_ID)),
+            Some(("docker".to_string(), DOCKER_ID.to_string()))
+        );
+        assert_eq!(
+            container(format!("/kubepods/burstable/pod1234/{}", DOCKER_ID)),
+            Some(("k8s".to_string(), DOCKER_ID.to_string()))
+        );
+        assert_eq!(
+            container(format!("/lxc/{}", DOCKER_ID)),
+            Some(("container".to_string(), DOCKER_ID.to_string()))
+        );
+        // Not 64 hex digits, so not a container id
+        assert_eq!(container("/docker/abc123".to_string()), None);
+        assert_eq!(container("/system.slice/cron.service".to_string()), None);
+    }
+
+    #[test]
+    fn systemd_units() {
+        assert_eq!(
+            parse_systemd_unit("/system.slice/nginx.service").as_deref(),
+            Some("nginx.service")
+        );
+        assert_eq!(
+            parse_systemd_unit("/user.slice/user-1000.slice/session-2.scope").as_deref(),
+            Some("session-2.scope")
+        );
+        // The innermost unit wins when units nest
+        assert_eq!(
+            parse_systemd_unit("/user.slice/user@1000.service/app.slice/app-foo.scope").as_deref(),
+            Some("app-foo.scope")
+        );
+        assert_eq!(parse_systemd_unit("/user.slice"), None);
+        assert_eq!(parse_systemd_unit("/"), None);
+    }
+
+    #[test]
+    fn labels_prefer_containers_then_units() {
+        let info = CgroupInfo::from_path(&format!("/system.slice/docker-{}.scope", DOCKER_ID));
+        assert_eq!(info.label(), "docker:0123456789ab");
+        assert_eq!(
+            CgroupInfo::from_path("/system.slice/cron.service").label(),
+            "cron.service"
+        );
+        assert_eq!(
+            CgroupInfo::from_path("/custom/group").label(),
+            "/custom/group"
+        );
+    }
+
+    #[test]
+    fn reads_from_proc_root() {
+        let root = env::temp_dir().join(format!("resource-manager-cgroup-{}", process::id()));
+        let dir = root.join("42");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("cgroup"), "0::/system.slice/cron.service\n").unwrap();
+        let info = read_process_cgroup(42, &root).unwrap();
+        assert_eq!(info.unit.as_deref(), Some("cron.service"));
+        assert!(read_process_cgroup(43, &root).is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
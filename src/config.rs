@@ -0,0 +1,54 @@
+use crate::alerts::Rule;
+use crate::disks::DiskFilter;
+use crate::events::EventsConfig;
+use crate::fleet::FleetConfig;
+use crate::keys::KeyConfig;
+use crate::pinned::PinMatcher;
+use crate::processes::ProcessesConfig;
+use crate::theme::ThemeConfig;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub alerts: Vec<Rule>,
+    pub pinned: Vec<PinMatcher>,
+    pub theme: Option<String>,
+    pub themes: HashMap<String, ThemeConfig>,
+    pub keys: HashMap<String, KeyConfig>,
+    pub disks: DiskFilter,
+    pub processes: ProcessesConfig,
+    pub events: EventsConfig,
+    pub fleet: FleetConfig,
+}
+
+// $XDG_CONFIG_HOME/resource-manager/config.toml, falling back to ~/.config
+pub fn default_config_path() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("resource-manager").join("config.toml"))
+}
+
+// An explicitly requested file has to exist; the default one is optional.
+pub fn load_config(path: Option<&Path>) -> Result<Config, String> {
+    let (path, required) = match path {
+        Some(path) => (path.to_path_buf(), true),
+        None => match default_config_path() {
+            Some(path) => (path, false),
+            None => return Ok(Config::default()),
+        },
+    };
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(_) if !required && !path.exists() => return Ok(Config::default()),
+        Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+    };
+    toml::from_str(&contents).map_err(|e| format!("invalid config {}: {}", path.display(), e))
+}
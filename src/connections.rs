@@ -0,0 +1,132 @@
+use crate::processes::ProcessInfo;
+use crate::sockets::{read_inet_sockets, socket_inode, Socket};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use sysinfo::Pid;
+
+pub const NET_ROOT: &str = "/proc/net";
+
+pub struct Connection {
+    pub socket: Socket,
+    // None when the owner is not visible, e.g. another user's process
+    // without root, or a socket that outlived its process
+    pub pid: Option<Pid>,
+    pub process: String,
+}
+
+// Identifies a row across refreshes. The inode alone is not enough: every
+// TIME_WAIT and orphaned socket has inode 0.
+#[derive(Clone, PartialEq)]
+pub struct ConnectionKey {
+    protocol: &'static str,
+    local: String,
+    remote: String,
+    state: &'static str,
+    inode: u64,
+}
+
+impl Connection {
+    pub fn key(&self) -> ConnectionKey {
+        ConnectionKey {
+            protocol: self.socket.protocol,
+            local: self.socket.local.clone(),
+            remote: self.socket.remote.clone(),
+            state: self.socket.state,
+            inode: self.socket.inode,
+        }
+    }
+
+    pub fn local_port(&self) -> Option<u16> {
+        port(&self.socket.local)
+    }
+
+    pub fn remote_port(&self) -> Option<u16> {
+        port(&self.socket.remote)
+    }
+
+    // A number matches either port exactly; anything else matches the
+    // protocol, state, addresses or process name, ignoring case
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim();
+        if let Ok(number) = query.parse::<u16>() {
+            return self.local_port() == Some(number) || self.remote_port() == Some(number);
+        }
+        let query = query.to_lowercase();
+        [
+            self.socket.protocol,
+            self.socket.state,
+            &self.socket.local,
+            &self.socket.remote,
+            &self.process,
+        ]
+        .iter()
+        .any(|field| field.to_lowercase().contains(&query))
+    }
+}
+
+// "127.0.0.1:8080" or "[::1]:8080"
+fn port(address: &str) -> Option<u16> {
+    address.rsplit_once(':')?.1.parse().ok()
+}
+
+// Socket inode to owning PID, from the fds of every listed process
+fn socket_owners(processes: &[ProcessInfo], proc_root: &Path) -> HashMap<u64, Pid> {
+    let mut owners = HashMap::new();
+    for p in processes.iter().filter(|p| !p.is_thread) {
+        let fd_dir = proc_root.join(p.pid.to_string()).join("fd");
+        let Ok(entries) = fs::read_dir(fd_dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let inode = fs::read_link(entry.path())
+                .ok()
+                .and_then(|target| socket_inode(&target.to_string_lossy()));
+            if let Some(inode) = inode {
+                owners.entry(inode).or_insert(p.pid);
+            }
+        }
+    }
+    owners
+}
+
+// Every TCP and UDP socket in our network namespace with its owner.
+// Listening sockets come first, then by protocol and local port.
+pub fn collect_connections(
+    processes: &[ProcessInfo],
+    net_dir: &Path,
+    proc_root: &Path,
+) -> Vec<Connection> {
+    let owners = socket_owners(processes, proc_root);
+    let names: HashMap<Pid, &str> = processes.iter().map(|p| (p.pid, p.name.as_str())).collect();
+    let mut connections: Vec<Connection> = read_inet_sockets(net_dir)
+        .into_iter()
+        .map(|socket| {
+            let pid = owners.get(&socket.inode).copied();
+            let process = pid
+                .and_then(|pid| names.get(&pid))
+                .map(|name| name.to_string())
+                .unwrap_or_default();
+            Connection {
+                socket,
+                pid,
+                process,
+            }
+        })
+        .collect();
+    connections.sort_by(|a, b| {
+        (
+            a.socket.state != "LISTEN",
+            a.socket.protocol,
+            a.local_port(),
+            &a.socket.local,
+        )
+            .cmp(&(
+                b.socket.state != "LISTEN",
+                b.socket.protocol,
+                b.local_port(),
+                &b.socket.local,
+            ))
+    });
+    connections
+}
@@ -0,0 +1,270 @@
+use crate::pinned::glob_match;
+use serde::{Deserialize, Serialize};
+use std::ffi::CString;
+use std::fs;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use sysinfo::Disks;
+
+pub const SYS_BLOCK_ROOT: &str = "/sys/class/block";
+
+// Which mounts the disk views show. A disk matching an include rule is always
+// shown; otherwise it is hidden when it matches an exclude rule. Mount rules
+// accept `*` and `?` wildcards.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct DiskFilter {
+    pub include_fs: Vec<String>,
+    pub exclude_fs: Vec<String>,
+    pub include_mounts: Vec<String>,
+    pub exclude_mounts: Vec<String>,
+}
+
+impl Default for DiskFilter {
+    fn default() -> DiskFilter {
+        let strings = |list: &[&str]| list.iter().map(|s| s.to_string()).collect();
+        DiskFilter {
+            include_fs: Vec::new(),
+            exclude_fs: strings(&[
+                "overlay",
+                "tmpfs",
+                "devtmpfs",
+                "squashfs",
+                "ramfs",
+                "autofs",
+                "fuse.snapfuse",
+                "fuse.lxcfs",
+                "nsfs",
+            ]),
+            include_mounts: Vec::new(),
+            exclude_mounts: strings(&[
+                "/snap/*",
+                "/var/snap/*",
+                "/var/lib/docker/*",
+                "/var/lib/containers/*",
+                // Runtime state, but not /run/media where removable media mounts
+                "/run/user/*",
+                "/run/snapd/*",
+                "/run/docker/*",
+                "/run/containerd/*",
+                "/run/netns/*",
+                "/run/credentials/*",
+            ]),
+        }
+    }
+}
+
+impl DiskFilter {
+    pub fn shows(&self, disk: &DiskInfo) -> bool {
+        let fs_in = |list: &[String]| list.contains(&disk.file_system);
+        let mount_in = |list: &[String]| {
+            list.iter()
+                .any(|pattern| glob_match(pattern, &disk.mount_point))
+        };
+        if fs_in(&self.include_fs) || mount_in(&self.include_mounts) {
+            return true;
+        }
+        !(fs_in(&self.exclude_fs) || mount_in(&self.exclude_mounts))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InodeUsage {
+    pub total: u64,
+    pub free: u64,
+}
+
+impl InodeUsage {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free)
+    }
+
+    pub fn usage(&self) -> f32 {
+        percent(self.used(), self.total)
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct DiskInfo {
+    pub name: String,
+    // Whole block device a partition belongs to, e.g. "nvme0n1" for
+    // /dev/nvme0n1p2; the device itself when it is not a partition
+    pub device: Option<String>,
+    pub mount_point: String,
+    // Further mount points of the same filesystem, folded into this entry
+    pub bind_mounts: Vec<String>,
+    pub file_system: String,
+    pub kind: String,
+    pub total_space: u64,
+    pub used_space: u64,
+    pub available_space: u64,
+    pub is_removable: bool,
+    pub is_read_only: bool,
+    // None when statvfs fails or the filesystem allocates inodes dynamically
+    pub inodes: Option<InodeUsage>,
+}
+
+impl DiskInfo {
+    pub fn usage(&self) -> f32 {
+        percent(self.used_space, self.total_space)
+    }
+
+    // "SSD, ro, removable" style summary of the kind and flags
+    pub fn flags(&self) -> String {
+        let mut flags = vec![self.kind.clone()];
+        if self.is_read_only {
+            flags.push(String::from("ro"));
+        }
+        if self.is_removable {
+            flags.push(String::from("removable"));
+        }
+        flags.join(", ")
+    }
+}
+
+fn percent(used: u64, total: u64) -> f32 {
+    if total == 0 {
+        return 0.0;
+    }
+    (used as f64 / total as f64 * 100.0) as f32
+}
+
+pub fn read_inodes(path: &Path) -> Option<InodeUsage> {
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: statvfs only writes into the zeroed struct we pass it, and the
+    // path is a valid NUL-terminated string for the duration of the call.
+    let mut stat: libc::statvfs = unsafe { mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    // btrfs and some network filesystems report no fixed inode table
+    if stat.f_files == 0 {
+        return None;
+    }
+    Some(InodeUsage {
+        total: stat.f_files as u64,
+        free: stat.f_ffree as u64,
+    })
+}
+
+// Resolves /dev/sda1, /dev/nvme0n1p2 or /dev/mapper/x (via its symlink) to
+// the whole device in /sys/class/block. Partitions have a "partition" file
+// and live in a directory named after their parent device.
+pub fn physical_device(name: &str, sys_root: &Path) -> Option<String> {
+    let dev = fs::canonicalize(name).unwrap_or_else(|_| Path::new(name).to_path_buf());
+    let short = dev.file_name()?.to_string_lossy().to_string();
+    let entry = sys_root.join(&short);
+    if !entry.exists() {
+        return None;
+    }
+    if !entry.join("partition").exists() {
+        return Some(short);
+    }
+    let resolved = fs::canonicalize(&entry).ok()?;
+    Some(
+        resolved
+            .parent()?
+            .file_name()?
+            .to_string_lossy()
+            .to_string(),
+    )
+}
+
+// The same filesystem mounted in several places (bind mounts, container
+// volumes) shows up once per mount point; keep the shortest mount point.
+fn dedupe_bind_mounts(disks: Vec<DiskInfo>) -> Vec<DiskInfo> {
+    let mut unique: Vec<DiskInfo> = Vec::new();
+    for disk in disks {
+        // Only real devices; pseudo filesystems reuse names like "tmpfs"
+        let existing = unique.iter_mut().find(|d| {
+            disk.name.starts_with("/dev/")
+                && d.name == disk.name
+                && d.total_space == disk.total_space
+        });
+        match existing {
+            Some(existing) if disk.mount_point.len() < existing.mount_point.len() => {
+                let mount = mem::replace(&mut existing.mount_point, disk.mount_point);
+                existing.bind_mounts.push(mount);
+            }
+            Some(existing) => existing.bind_mounts.push(disk.mount_point),
+            None => unique.push(disk),
+        }
+    }
+    unique
+}
+
+pub fn collect_disks(filter: &DiskFilter, sys_root: &Path) -> Vec<DiskInfo> {
+    let disks = Disks::new_with_refreshed_list();
+    let disks: Vec<DiskInfo> = disks
+        .list()
+        .iter()
+        .filter_map(|disk| {
+            let total_space = disk.total_space();
+            let available_space = disk.available_space();
+            let name = disk.name().to_string_lossy().to_string();
+            let mut info = DiskInfo {
+                device: physical_device(&name, sys_root),
+                name,
+                mount_point: disk.mount_point().to_string_lossy().to_string(),
+                bind_mounts: Vec::new(),
+                file_system: disk.file_system().to_string_lossy().to_string(),
+                kind: disk.kind().to_string(),
+                total_space,
+                // Reserved blocks make available < free, so this can't go
+                // negative, but don't trust every filesystem on that
+                used_space: total_space.saturating_sub(available_space),
+                available_space,
+                is_removable: disk.is_removable(),
+                is_read_only: disk.is_read_only(),
+                inodes: None,
+            };
+            // statvfs can hang on a dead network mount, so only call it for
+            // the disks that are shown
+            if !filter.shows(&info) {
+                return None;
+            }
+            info.inodes = read_inodes(disk.mount_point());
+            Some(info)
+        })
+        .collect();
+    let mut disks = dedupe_bind_mounts(disks);
+    // Partitions of one device end up next to each other
+    disks.sort_by(|a, b| (&a.device, &a.name).cmp(&(&b.device, &b.name)));
+    disks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn disk(file_system: &str, mount_point: &str) -> DiskInfo {
+        DiskInfo {
+            file_system: file_system.to_string(),
+            mount_point: mount_point.to_string(),
+            ..DiskInfo::default()
+        }
+    }
+
+    #[test]
+    fn default_filter() {
+        let filter = DiskFilter::default();
+        assert!(filter.shows(&disk("ext4", "/")));
+        assert!(filter.shows(&disk("vfat", "/run/media/alice/USB")));
+        assert!(!filter.shows(&disk("ext4", "/run/user/1000/doc")));
+        assert!(!filter.shows(&disk("ext4", "/var/lib/docker/volumes/data")));
+        assert!(!filter.shows(&disk("tmpfs", "/tmp")));
+    }
+
+    #[test]
+    fn includes_win_over_excludes() {
+        let filter = DiskFilter {
+            include_fs: vec![String::from("tmpfs")],
+            include_mounts: vec![String::from("/run/user/*/gvfs")],
+            ..DiskFilter::default()
+        };
+        assert!(filter.shows(&disk("tmpfs", "/tmp")));
+        assert!(filter.shows(&disk("fuse", "/run/user/1000/gvfs")));
+        assert!(!filter.shows(&disk("fuse", "/run/user/1000/doc")));
+    }
+}
@@ -0,0 +1,201 @@
+use crate::processes::ProcessInfo;
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use sysinfo::Pid;
+
+// Oldest events are dropped past this many
+pub const MAX_EVENTS: usize = 1000;
+
+// `[events]` in the config
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EventsConfig {
+    // Every event is also appended here, one line each
+    pub log: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum EventKind {
+    Start,
+    Exit,
+}
+
+impl EventKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            EventKind::Start => "START",
+            EventKind::Exit => "EXIT",
+        }
+    }
+}
+
+pub struct ProcessEvent {
+    // Seconds since the epoch
+    pub timestamp: u64,
+    pub kind: EventKind,
+    pub pid: Pid,
+    pub parent: Option<Pid>,
+    pub name: String,
+    // Seconds from start to exit; None for start events
+    pub lifetime: Option<u64>,
+    // Largest RSS seen while the process was tracked, in bytes
+    pub peak_memory: u64,
+}
+
+impl ProcessEvent {
+    fn log_line(&self, host: Option<&str>) -> String {
+        let parent = match self.parent {
+            Some(pid) => pid.to_string(),
+            None => String::from("-"),
+        };
+        let host = match host {
+            Some(host) => format!(" host={}", host),
+            None => String::new(),
+        };
+        let mut line = format!(
+            "{} {}{} pid={} parent={} name={}",
+            self.timestamp,
+            self.kind.title(),
+            host,
+            self.pid,
+            parent,
+            self.name
+        );
+        if let Some(lifetime) = self.lifetime {
+            line.push_str(&format!(
+                " lifetime={}s peak_rss={}",
+                lifetime, self.peak_memory
+            ));
+        }
+        line.push('\n');
+        line
+    }
+}
+
+struct Tracked {
+    parent: Option<Pid>,
+    name: String,
+    start_time: u64,
+    peak_memory: u64,
+}
+
+// Diffs each refresh's processes against the previous one. A PID is keyed
+// together with its start time so a reused PID counts as a new process.
+pub struct EventLog {
+    pub events: VecDeque<ProcessEvent>,
+    pub last_error: Option<String>,
+    log: Option<PathBuf>,
+    // Labels log lines when several hosts share the log
+    host: Option<String>,
+    tracked: HashMap<(Pid, u64), Tracked>,
+    // The first refresh only sets the baseline, rather than reporting
+    // every running process as started
+    primed: bool,
+}
+
+fn epoch_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl EventLog {
+    pub fn new(config: EventsConfig) -> EventLog {
+        EventLog {
+            events: VecDeque::new(),
+            last_error: None,
+            log: config.log,
+            host: None,
+            tracked: HashMap::new(),
+            primed: false,
+        }
+    }
+
+    pub fn set_host(&mut self, host: String) {
+        self.host = Some(host);
+    }
+
+    pub fn update(&mut self, processes: &[ProcessInfo], now: SystemTime) {
+        let timestamp = epoch_secs(now);
+        let mut seen = HashMap::new();
+        let mut new_events = Vec::new();
+        // Threads come and go with their process, so only leaders count
+        for p in processes.iter().filter(|p| !p.is_thread) {
+            let key = (p.pid, p.start_time);
+            let tracked = match self.tracked.remove(&key) {
+                Some(mut tracked) => {
+                    tracked.peak_memory = tracked.peak_memory.max(p.memory);
+                    tracked
+                }
+                None => {
+                    if self.primed {
+                        new_events.push(ProcessEvent {
+                            timestamp,
+                            kind: EventKind::Start,
+                            pid: p.pid,
+                            parent: p.parent,
+                            name: p.name.clone(),
+                            lifetime: None,
+                            peak_memory: p.memory,
+                        });
+                    }
+                    Tracked {
+                        parent: p.parent,
+                        name: p.name.clone(),
+                        start_time: p.start_time,
+                        peak_memory: p.memory,
+                    }
+                }
+            };
+            seen.insert(key, tracked);
+        }
+        // Whatever was not seen again has exited
+        let mut exited: Vec<((Pid, u64), Tracked)> = self.tracked.drain().collect();
+        exited.sort_by_key(|((pid, _), _)| *pid);
+        for ((pid, _), tracked) in exited {
+            new_events.push(ProcessEvent {
+                timestamp,
+                kind: EventKind::Exit,
+                pid,
+                parent: tracked.parent,
+                name: tracked.name,
+                lifetime: Some(timestamp.saturating_sub(tracked.start_time)),
+                peak_memory: tracked.peak_memory,
+            });
+        }
+        self.tracked = seen;
+        self.primed = true;
+
+        if let Some(path) = &self.log {
+            if !new_events.is_empty() {
+                let lines: String = new_events
+                    .iter()
+                    .map(|e| e.log_line(self.host.as_deref()))
+                    .collect();
+                if let Err(e) = append(path, &lines) {
+                    self.last_error = Some(e);
+                }
+            }
+        }
+        for event in new_events {
+            if self.events.len() == MAX_EVENTS {
+                self.events.pop_front();
+            }
+            self.events.push_back(event);
+        }
+    }
+}
+
+fn append(path: &PathBuf, lines: &str) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("cannot open {}: {}", path.display(), e))?;
+    file.write_all(lines.as_bytes())
+        .map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
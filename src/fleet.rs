@@ -0,0 +1,337 @@
+use crate::alerts::{AlertEngine, AlertState};
+use crate::app::{contains, Clicks, Search};
+use crate::keys::Action;
+use crate::remote::RemoteStatus;
+use crate::snapshot::Snapshot;
+use crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::time::{Duration, SystemTime};
+use tui::layout::Rect;
+
+// A host without a new snapshot for this long is shown as stale; the agent
+// normally sends one every tick
+pub const STALE_AFTER: Duration = Duration::from_secs(3);
+
+// `[fleet]` in the config
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FleetConfig {
+    // Agent addresses, host:port or unix:<path>
+    pub hosts: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FleetColumn {
+    Host,
+    Address,
+    Status,
+    Cpu,
+    Memory,
+    Load,
+    Disk,
+    Alerts,
+}
+
+pub const FLEET_COLUMNS: [FleetColumn; 8] = [
+    FleetColumn::Host,
+    FleetColumn::Address,
+    FleetColumn::Status,
+    FleetColumn::Cpu,
+    FleetColumn::Memory,
+    FleetColumn::Load,
+    FleetColumn::Disk,
+    FleetColumn::Alerts,
+];
+
+impl FleetColumn {
+    pub fn title(&self) -> &'static str {
+        match self {
+            FleetColumn::Host => "Host",
+            FleetColumn::Address => "Address",
+            FleetColumn::Status => "Status",
+            FleetColumn::Cpu => "CPU",
+            FleetColumn::Memory => "Memory",
+            FleetColumn::Load => "Load (1/5/15)",
+            FleetColumn::Disk => "Fullest disk",
+            FleetColumn::Alerts => "Alerts",
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        match self {
+            FleetColumn::Host => 20,    // hostname from the agent's hello
+            FleetColumn::Address => 22, // "lab-01.example.org:7878"
+            FleetColumn::Status => 22,  // "stale, seen 14:03:22"
+            FleetColumn::Cpu => 8,      // "100.0%"
+            FleetColumn::Memory => 8,
+            FleetColumn::Load => 16,   // "12.34 10.01 9.87"
+            FleetColumn::Disk => 22,   // "97.1% /var/lib/docker"
+            FleetColumn::Alerts => 10, // "2 pending"
+        }
+    }
+
+    // Names sort A-Z, everything else worst first
+    fn descending_by_default(&self) -> bool {
+        !matches!(self, FleetColumn::Host | FleetColumn::Address)
+    }
+}
+
+// Up: fresh data. Stale: had data, none lately. Unreachable: never had any.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HostState {
+    Up,
+    Stale,
+    Unreachable,
+}
+
+// One host's line in the fleet table
+pub struct FleetRow {
+    // Position in the config's host list, which identifies the host
+    pub index: usize,
+    // The agent's hostname once it has said hello, else "-"
+    pub name: String,
+    pub address: String,
+    pub state: HostState,
+    pub last_seen: Option<SystemTime>,
+    pub error: Option<String>,
+    // The last snapshot's figures, kept while the host is stale
+    pub cpu: f32,
+    pub memory: f32,
+    pub load: [f64; 3],
+    pub disk: Option<(String, f32)>,
+    pub firing: usize,
+    pub pending: usize,
+}
+
+impl FleetRow {
+    pub fn new(
+        index: usize,
+        status: &RemoteStatus,
+        snapshot: &Snapshot,
+        alerts: &AlertEngine,
+    ) -> FleetRow {
+        let state = match status.last_seen {
+            Some(at) if status.connected && at.elapsed() < STALE_AFTER => HostState::Up,
+            Some(_) => HostState::Stale,
+            None => HostState::Unreachable,
+        };
+        let stats = &snapshot.stats;
+        let memory = if stats.total_memory > 0 {
+            (stats.used_memory as f64 / stats.total_memory as f64 * 100.0) as f32
+        } else {
+            0.0
+        };
+        let disk = snapshot
+            .disks
+            .iter()
+            .max_by(|a, b| a.usage().total_cmp(&b.usage()))
+            .map(|d| (d.mount_point.clone(), d.usage()));
+        let count = |firing: bool| {
+            alerts
+                .alerts
+                .iter()
+                .filter(|a| match a.state {
+                    AlertState::Firing(_) => firing,
+                    AlertState::Pending(_) => !firing,
+                    AlertState::Ok => false,
+                })
+                .count()
+        };
+        FleetRow {
+            index,
+            name: status.host.clone().unwrap_or_else(|| String::from("-")),
+            address: status.address.clone(),
+            state,
+            last_seen: status
+                .last_seen
+                .and_then(|at| SystemTime::now().checked_sub(at.elapsed())),
+            error: status.error.clone(),
+            cpu: stats.cpu_global_usage,
+            memory,
+            load: stats.load_average,
+            disk,
+            firing: count(true),
+            pending: count(false),
+        }
+    }
+
+    fn has_data(&self) -> bool {
+        self.state != HostState::Unreachable
+    }
+}
+
+fn compare(a: &FleetRow, b: &FleetRow, column: FleetColumn) -> Ordering {
+    match column {
+        FleetColumn::Host => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        FleetColumn::Address => a.address.cmp(&b.address),
+        FleetColumn::Status => a.state.cmp(&b.state),
+        FleetColumn::Cpu => a.cpu.total_cmp(&b.cpu),
+        FleetColumn::Memory => a.memory.total_cmp(&b.memory),
+        FleetColumn::Load => a.load[0].total_cmp(&b.load[0]),
+        FleetColumn::Disk => {
+            let usage = |row: &FleetRow| row.disk.as_ref().map_or(0.0, |(_, usage)| *usage);
+            usage(a).total_cmp(&usage(b))
+        }
+        FleetColumn::Alerts => (a.firing, a.pending).cmp(&(b.firing, b.pending)),
+    }
+}
+
+// The fleet table's sorting, filter, selection and click targets
+pub struct FleetView {
+    pub sort: FleetColumn,
+    pub descending: bool,
+    // Matches the hostname or address, ignoring case
+    pub search: Search,
+    // Selection follows the host's index so it survives re-sorting
+    pub selected: Option<usize>,
+    pub scroll: usize,
+    pub header: Rect,
+    // x offset and width of each column, in FLEET_COLUMNS order
+    pub columns: Vec<(u16, u16)>,
+    pub rows: Rect,
+    clicks: Clicks,
+}
+
+impl FleetView {
+    pub fn new() -> FleetView {
+        FleetView {
+            sort: FleetColumn::Host,
+            descending: false,
+            search: Search::default(),
+            selected: None,
+            scroll: 0,
+            header: Rect::default(),
+            columns: Vec::new(),
+            rows: Rect::default(),
+            clicks: Clicks::default(),
+        }
+    }
+
+    // Filtered and sorted. Hosts that never answered have no figures to
+    // compare, so they go last unless sorting by status.
+    pub fn visible(&self, mut rows: Vec<FleetRow>) -> Vec<FleetRow> {
+        let query = self.search.query.to_lowercase();
+        rows.retain(|row| {
+            row.name.to_lowercase().contains(&query) || row.address.to_lowercase().contains(&query)
+        });
+        rows.sort_by(|a, b| {
+            let order = compare(a, b, self.sort);
+            let order = if self.descending {
+                order.reverse()
+            } else {
+                order
+            };
+            if self.sort == FleetColumn::Status {
+                return order;
+            }
+            b.has_data().cmp(&a.has_data()).then(order)
+        });
+        rows
+    }
+
+    pub fn selected_position(&self, rows: &[FleetRow]) -> Option<usize> {
+        let index = self.selected?;
+        rows.iter().position(|row| row.index == index)
+    }
+
+    fn move_selection(&mut self, rows: &[FleetRow], delta: isize) {
+        if rows.is_empty() {
+            self.selected = None;
+            return;
+        }
+        let last = rows.len() as isize - 1;
+        let position = match self.selected_position(rows) {
+            Some(position) => (position as isize + delta).clamp(0, last),
+            None if delta < 0 => last,
+            None => 0,
+        };
+        self.selected = Some(rows[position as usize].index);
+    }
+
+    fn set_sort(&mut self, column: FleetColumn) {
+        if self.sort == column {
+            self.descending = !self.descending;
+        } else {
+            self.sort = column;
+            self.descending = column.descending_by_default();
+        }
+    }
+
+    fn sort_next(&mut self) {
+        let position = FLEET_COLUMNS
+            .iter()
+            .position(|c| *c == self.sort)
+            .unwrap_or(0);
+        self.sort = FLEET_COLUMNS[(position + 1) % FLEET_COLUMNS.len()];
+        self.descending = self.sort.descending_by_default();
+    }
+
+    // Takes the key while the filter is being typed
+    pub fn edit_search(&mut self, event: &KeyEvent) -> bool {
+        let consumed = self.search.edit(event);
+        if consumed {
+            self.scroll = 0;
+        }
+        consumed
+    }
+
+    // Returns the host to open, if the action opens one
+    pub fn apply(&mut self, action: Action, rows: &[FleetRow]) -> Option<usize> {
+        match action {
+            // Only a host the filter still shows can be opened
+            Action::Details => return self.selected_position(rows).and(self.selected),
+            Action::Search => self.search.editing = true,
+            Action::SortNext => self.sort_next(),
+            Action::SortReverse => self.descending = !self.descending,
+            Action::Up => self.move_selection(rows, -1),
+            Action::Down => self.move_selection(rows, 1),
+            Action::PageUp => self.move_selection(rows, -20),
+            Action::PageDown => self.move_selection(rows, 20),
+            Action::Home => self.move_selection(rows, isize::MIN / 2),
+            Action::End => self.move_selection(rows, isize::MAX / 2),
+            _ => {}
+        }
+        None
+    }
+
+    // Esc clears the filter first; false when there was none
+    pub fn back(&mut self) -> bool {
+        if self.search.query.is_empty() {
+            return false;
+        }
+        self.search.clear();
+        true
+    }
+
+    // Returns the host to open on a double-click
+    pub fn handle_mouse(&mut self, event: MouseEvent, rows: &[FleetRow]) -> Option<usize> {
+        let (column, row) = (event.column, event.row);
+        match event.kind {
+            MouseEventKind::ScrollUp => self.move_selection(rows, -3),
+            MouseEventKind::ScrollDown => self.move_selection(rows, 3),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let double = self.clicks.is_double(column, row);
+                if contains(self.header, column, row) {
+                    let clicked = self
+                        .columns
+                        .iter()
+                        .position(|(x, width)| column >= *x && column < x + width);
+                    if let Some(index) = clicked {
+                        self.set_sort(FLEET_COLUMNS[index]);
+                    }
+                } else if contains(self.rows, column, row) {
+                    let position = self.scroll + (row - self.rows.y) as usize;
+                    if let Some(clicked) = rows.get(position) {
+                        self.selected = Some(clicked.index);
+                        if double {
+                            return Some(clicked.index);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
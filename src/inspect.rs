@@ -0,0 +1,147 @@
+use crate::sockets::{read_sockets, socket_inode, Socket};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+
+pub struct FdInfo {
+    pub fd: u32,
+    pub kind: &'static str,
+    pub target: String,
+}
+
+pub struct MapInfo {
+    pub range: String,
+    pub perms: String,
+    pub path: String,
+    // Resident and proportional set size in bytes
+    pub rss: u64,
+    pub pss: u64,
+}
+
+// Totals over every mapping, from smaps_rollup
+pub struct MemoryRollup {
+    pub rss: u64,
+    pub pss: u64,
+    // Pages no other process maps
+    pub uss: u64,
+    pub swap: u64,
+}
+
+// Pseudo targets are named after their kind ("pipe:[123]",
+// "anon_inode:[eventfd]"); anything else is a path to stat
+fn fd_kind(link: &Path, target: &str) -> &'static str {
+    if target.starts_with("socket:") {
+        return "socket";
+    }
+    if target.starts_with("pipe:") {
+        return "pipe";
+    }
+    if target.starts_with("anon_inode:") {
+        return "anon";
+    }
+    match fs::metadata(link) {
+        Ok(meta) if meta.is_dir() => "dir",
+        Ok(meta) if meta.file_type().is_char_device() => "char",
+        Ok(meta) if meta.file_type().is_block_device() => "block",
+        Ok(meta) if meta.file_type().is_fifo() => "fifo",
+        Ok(meta) if meta.file_type().is_socket() => "socket",
+        Ok(_) => "file",
+        Err(_) => "unknown",
+    }
+}
+
+// Reading another user's fds needs the same privileges as ptrace, so
+// permission errors are returned for the UI to show
+pub fn read_fds(pid: u32, root: &Path) -> io::Result<Vec<FdInfo>> {
+    let mut fds = Vec::new();
+    for entry in fs::read_dir(root.join(pid.to_string()).join("fd"))?.flatten() {
+        let Some(fd) = entry.file_name().to_str().and_then(|s| s.parse().ok()) else {
+            continue;
+        };
+        let link = entry.path();
+        // The fd may be closed between listing and reading
+        let target = match fs::read_link(&link) {
+            Ok(target) => target,
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied => return Err(e),
+            Err(_) => continue,
+        };
+        let target = target.to_string_lossy().to_string();
+        fds.push(FdInfo {
+            fd,
+            kind: fd_kind(&link, &target),
+            target,
+        });
+    }
+    fds.sort_by_key(|f| f.fd);
+    Ok(fds)
+}
+
+// The process's sockets, found by joining its fd inodes against the socket
+// tables of its own network namespace
+pub fn read_process_sockets(pid: u32, root: &Path) -> io::Result<Vec<Socket>> {
+    let inodes: HashSet<u64> = read_fds(pid, root)?
+        .iter()
+        .filter_map(|fd| socket_inode(&fd.target))
+        .collect();
+    let net_dir = root.join(pid.to_string()).join("net");
+    Ok(read_sockets(&net_dir)
+        .into_iter()
+        .filter(|socket| inodes.contains(&socket.inode))
+        .collect())
+}
+
+// "Rss:   1668 kB" -> bytes
+fn parse_kb(line: &str) -> Option<u64> {
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+// smaps lists each mapping's header line followed by "Key: value" fields
+pub fn read_maps(pid: u32, root: &Path) -> io::Result<Vec<MapInfo>> {
+    let contents = fs::read_to_string(root.join(pid.to_string()).join("smaps"))?;
+    let mut maps: Vec<MapInfo> = Vec::new();
+    for line in contents.lines() {
+        let Some(first) = line.split_whitespace().next() else {
+            continue;
+        };
+        if !first.ends_with(':') {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            maps.push(MapInfo {
+                range: first.to_string(),
+                perms: fields.get(1).unwrap_or(&"").to_string(),
+                path: fields.get(5..).map(|p| p.join(" ")).unwrap_or_default(),
+                rss: 0,
+                pss: 0,
+            });
+            continue;
+        }
+        let Some(map) = maps.last_mut() else {
+            continue;
+        };
+        match first {
+            "Rss:" => map.rss = parse_kb(line).unwrap_or(0),
+            "Pss:" => map.pss = parse_kb(line).unwrap_or(0),
+            _ => {}
+        }
+    }
+    Ok(maps)
+}
+
+pub fn read_rollup(pid: u32, root: &Path) -> Option<MemoryRollup> {
+    let contents = fs::read_to_string(root.join(pid.to_string()).join("smaps_rollup")).ok()?;
+    let field = |name: &str| {
+        contents
+            .lines()
+            .find(|line| line.starts_with(name))
+            .and_then(parse_kb)
+            .unwrap_or(0)
+    };
+    Some(MemoryRollup {
+        rss: field("Rss:"),
+        pss: field("Pss:"),
+        uss: field("Private_Clean:") + field("Private_Dirty:") + field("Private_Hugetlb:"),
+        swap: field("Swap:"),
+    })
+}
@@ -0,0 +1,452 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    Quit,
+    // Closes the open overlay, or quits when there is none
+    Back,
+    Help,
+    Details,
+    Threads,
+    Search,
+    ToggleGroups,
+    TogglePin,
+    ToggleMouse,
+    Suspend,
+    SortNext,
+    SortReverse,
+    ToggleCpuMode,
+    NextTab,
+    PreviousTab,
+    SelectTab(usize),
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+}
+
+// Every action with its config name and help text, in help overlay order
+pub const ACTIONS: [(Action, &str, &str); 30] = [
+    (Action::Quit, "quit", "Quit"),
+    (
+        Action::Back,
+        "back",
+        "Close the overlay, go back to the fleet, or quit",
+    ),
+    (Action::Help, "help", "Show or hide this help"),
+    (
+        Action::Details,
+        "details",
+        "Open the selected process's details, the CPU topology, or a fleet host",
+    ),
+    (
+        Action::Threads,
+        "threads",
+        "Show the selected process's threads",
+    ),
+    (
+        Action::Search,
+        "search",
+        "Filter the connections, files, sockets or maps",
+    ),
+    (
+        Action::ToggleGroups,
+        "toggle-groups",
+        "Switch between processes and groups",
+    ),
+    (
+        Action::TogglePin,
+        "toggle-pin",
+        "Pin or unpin the selected process",
+    ),
+    (
+        Action::ToggleMouse,
+        "toggle-mouse",
+        "Release or capture the mouse",
+    ),
+    (
+        Action::Suspend,
+        "suspend",
+        "Suspend to the shell (resume with fg)",
+    ),
+    (Action::SortNext, "sort-next", "Sort by the next column"),
+    (
+        Action::SortReverse,
+        "sort-reverse",
+        "Reverse the sort order",
+    ),
+    (
+        Action::ToggleCpuMode,
+        "cpu-mode",
+        "Show process CPU as a share of one core or of the machine",
+    ),
+    (Action::NextTab, "next-tab", "Next tab"),
+    (Action::PreviousTab, "previous-tab", "Previous tab"),
+    (Action::SelectTab(0), "tab-1", "Overview tab"),
+    (Action::SelectTab(1), "tab-2", "Processes tab"),
+    (Action::SelectTab(2), "tab-3", "CPU tab"),
+    (Action::SelectTab(3), "tab-4", "Memory tab"),
+    (Action::SelectTab(4), "tab-5", "Disks tab"),
+    (Action::SelectTab(5), "tab-6", "Network tab"),
+    (Action::SelectTab(6), "tab-7", "Sensors tab"),
+    (Action::SelectTab(7), "tab-8", "Connections tab"),
+    (Action::SelectTab(8), "tab-9", "Events tab"),
+    (Action::Up, "up", "Select the previous process"),
+    (Action::Down, "down", "Select the next process"),
+    (Action::PageUp, "page-up", "Move the selection up a page"),
+    (
+        Action::PageDown,
+        "page-down",
+        "Move the selection down a page",
+    ),
+    (Action::Home, "home", "Select the first process"),
+    (Action::End, "end", "Select the last process"),
+];
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        ACTIONS
+            .iter()
+            .find(|(action, _, _)| action == self)
+            .map(|(_, name, _)| *name)
+            .unwrap_or("")
+    }
+
+    fn from_name(name: &str) -> Option<Action> {
+        ACTIONS
+            .iter()
+            .find(|(_, n, _)| *n == name)
+            .map(|(action, _, _)| *action)
+    }
+}
+
+// A key press with the modifiers that matter for matching. Shift is folded
+// into the character (or BackTab) so "G" and "Shift-g" are the same key.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        let control = modifiers.contains(KeyModifiers::CONTROL);
+        let code = match code {
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            // Terminals report Ctrl-Shift-x and Ctrl-x alike
+            KeyCode::Char(c) if control => KeyCode::Char(c.to_ascii_lowercase()),
+            code => code,
+        };
+        Key {
+            code,
+            modifiers: modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+
+    pub fn from_event(event: &KeyEvent) -> Key {
+        Key::new(event.code, event.modifiers)
+    }
+
+    // Accepts "q", "G", "Space", "Esc", "Shift-Tab", "Ctrl-c", "Alt-x", "F1", ...
+    pub fn parse(s: &str) -> Option<Key> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s.trim();
+        let mut shift = false;
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            let Some(prefix) = ["ctrl-", "alt-", "shift-"]
+                .into_iter()
+                .find(|p| lower.starts_with(p) && rest.len() > p.len())
+            else {
+                break;
+            };
+            match prefix {
+                "ctrl-" => modifiers |= KeyModifiers::CONTROL,
+                "alt-" => modifiers |= KeyModifiers::ALT,
+                _ => shift = true,
+            }
+            rest = &rest[prefix.len()..];
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if shift => KeyCode::Char(c.to_ascii_uppercase()),
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" if shift => KeyCode::BackTab,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "insert" => KeyCode::Insert,
+                "delete" | "del" => KeyCode::Delete,
+                f => KeyCode::F(f.strip_prefix('f')?.parse().ok()?),
+            },
+        };
+        Some(Key::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::BackTab => write!(f, "Shift-Tab"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+// `[keys]` in the config: an action name mapped to one key or a list of
+// keys, replacing that action's defaults. An empty list unbinds it.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum KeyConfig {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Clone)]
+pub struct Keymap {
+    bindings: Vec<(Key, Action)>,
+}
+
+impl Keymap {
+    pub fn defaults() -> Keymap {
+        let key = |code| Key::new(code, KeyModifiers::NONE);
+        let mut bindings = vec![
+            (key(KeyCode::Char('q')), Action::Quit),
+            (
+                Key::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+                Action::Quit,
+            ),
+            (key(KeyCode::Esc), Action::Back),
+            (key(KeyCode::Char('?')), Action::Help),
+            (key(KeyCode::F(1)), Action::Help),
+            (key(KeyCode::Enter), Action::Details),
+            (key(KeyCode::Char('t')), Action::Threads),
+            (key(KeyCode::Char('/')), Action::Search),
+            (key(KeyCode::Char('g')), Action::ToggleGroups),
+            (key(KeyCode::Char('p')), Action::TogglePin),
+            (key(KeyCode::Char('m')), Action::ToggleMouse),
+            (
+                Key::new(KeyCode::Char('z'), KeyModifiers::CONTROL),
+                Action::Suspend,
+            ),
+            (key(KeyCode::Char('s')), Action::SortNext),
+            (key(KeyCode::Char('r')), Action::SortReverse),
+            (key(KeyCode::Char('I')), Action::ToggleCpuMode),
+            (key(KeyCode::Tab), Action::NextTab),
+            (key(KeyCode::BackTab), Action::PreviousTab),
+            (key(KeyCode::Up), Action::Up),
+            (key(KeyCode::Char('k')), Action::Up),
+            (key(KeyCode::Down), Action::Down),
+            (key(KeyCode::Char('j')), Action::Down),
+            (key(KeyCode::PageUp), Action::PageUp),
+            (key(KeyCode::PageDown), Action::PageDown),
+            (key(KeyCode::Home), Action::Home),
+            (key(KeyCode::End), Action::End),
+        ];
+        for i in 0..9 {
+            let digit = char::from_digit(i as u32 + 1, 10).unwrap_or('1');
+            bindings.push((key(KeyCode::Char(digit)), Action::SelectTab(i)));
+        }
+        Keymap { bindings }
+    }
+
+    // Applies config overrides on top of the defaults. A key bound to two
+    // actions is an error rather than letting one silently shadow the other.
+    pub fn from_config(overrides: &HashMap<String, KeyConfig>) -> Result<Keymap, String> {
+        let mut keymap = Keymap::defaults();
+        let mut names: Vec<&String> = overrides.keys().collect();
+        names.sort();
+        for name in names {
+            let action = Action::from_name(name)
+                .ok_or_else(|| format!("keys: unknown action \"{}\"", name))?;
+            let keys = match &overrides[name] {
+                KeyConfig::One(key) => vec![key.clone()],
+                KeyConfig::Many(keys) => keys.clone(),
+            };
+            keymap.bindings.retain(|(_, a)| *a != action);
+            for key in keys {
+                let parsed = Key::parse(&key)
+                    .ok_or_else(|| format!("keys: invalid key \"{}\" for {}", key, name))?;
+                keymap.bindings.push((parsed, action));
+            }
+        }
+
+        for (i, (key, action)) in keymap.bindings.iter().enumerate() {
+            let conflict = keymap.bindings[i + 1..]
+                .iter()
+                .find(|(k, a)| k == key && a != action);
+            if let Some((_, other)) = conflict {
+                return Err(format!(
+                    "keys: {} is bound to both {} and {}",
+                    key,
+                    action.name(),
+                    other.name()
+                ));
+            }
+        }
+        Ok(keymap)
+    }
+
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        let key = Key::from_event(event);
+        self.bindings
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, action)| *action)
+    }
+
+    pub fn keys_for(&self, action: Action) -> Vec<Key> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    // The first key for an action, as shown in hints
+    pub fn hint(&self, action: Action) -> Option<String> {
+        self.keys_for(action).first().map(|key| key.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The `[keys]` table as it would appear in the config
+    fn overrides(toml: &str) -> HashMap<String, KeyConfig> {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn parses_modifiers() {
+        let ctrl_c = Key::parse("Ctrl-c").unwrap();
+        assert!(ctrl_c == Key::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        // Case of the prefix and of a Ctrl letter does not matter
+        assert!(Key::parse("ctrl-C").unwrap() == ctrl_c);
+
+        let alt_x = Key::parse("Alt-x").unwrap();
+        assert!(alt_x == Key::new(KeyCode::Char('x'), KeyModifiers::ALT));
+        let both = Key::parse("Ctrl-Alt-x").unwrap();
+        assert!(both.modifiers == KeyModifiers::CONTROL | KeyModifiers::ALT);
+
+        assert!(Key::parse("Shift-Tab").unwrap().code == KeyCode::BackTab);
+        assert!(Key::parse("BackTab").unwrap().code == KeyCode::BackTab);
+        assert!(Key::parse("Shift-g").unwrap() == Key::parse("G").unwrap());
+        // A lone "-" is the key itself, not an empty prefix
+        assert!(Key::parse("Ctrl--").unwrap().code == KeyCode::Char('-'));
+    }
+
+    #[test]
+    fn parses_named_keys() {
+        assert!(Key::parse("Space").unwrap().code == KeyCode::Char(' '));
+        assert!(Key::parse("esc").unwrap().code == KeyCode::Esc);
+        assert!(Key::parse("PageDown").unwrap().code == KeyCode::PageDown);
+        assert!(Key::parse("F12").unwrap().code == KeyCode::F(12));
+        assert!(Key::parse("").is_none());
+        assert!(Key::parse("Hyper-x").is_none());
+        assert!(Key::parse("Fx").is_none());
+    }
+
+    #[test]
+    fn events_match_parsed_keys() {
+        let keymap = Keymap::defaults();
+        let shift_tab = press(KeyCode::Tab, KeyModifiers::SHIFT);
+        assert!(keymap.action(&shift_tab) == Some(Action::PreviousTab));
+        let ctrl_shift_c = press(
+            KeyCode::Char('C'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        );
+        assert!(keymap.action(&ctrl_shift_c) == Some(Action::Quit));
+        let shift_i = press(KeyCode::Char('I'), KeyModifiers::SHIFT);
+        assert!(keymap.action(&shift_i) == Some(Action::ToggleCpuMode));
+    }
+
+    #[test]
+    fn overrides_replace_defaults() {
+        let config = overrides("quit = [\"Ctrl-q\"]\nhelp = \"h\"");
+        let keymap = Keymap::from_config(&config).unwrap();
+        assert!(keymap
+            .action(&press(KeyCode::Char('q'), KeyModifiers::NONE))
+            .is_none());
+        assert!(
+            keymap.action(&press(KeyCode::Char('q'), KeyModifiers::CONTROL)) == Some(Action::Quit)
+        );
+        assert_eq!(keymap.hint(Action::Help).as_deref(), Some("h"));
+        assert_eq!(keymap.keys_for(Action::Help).len(), 1);
+    }
+
+    #[test]
+    fn empty_list_unbinds() {
+        let config = overrides("toggle-mouse = []");
+        let keymap = Keymap::from_config(&config).unwrap();
+        assert!(keymap.keys_for(Action::ToggleMouse).is_empty());
+        assert!(keymap.hint(Action::ToggleMouse).is_none());
+        assert!(keymap
+            .action(&press(KeyCode::Char('m'), KeyModifiers::NONE))
+            .is_none());
+    }
+
+    #[test]
+    fn rejects_conflicts() {
+        let config = overrides("help = \"q\"");
+        assert_eq!(
+            Keymap::from_config(&config).err().as_deref(),
+            Some("keys: q is bound to both quit and help")
+        );
+        // Moving the default out of the way resolves it
+        let config = overrides("help = \"q\"\nquit = \"x\"");
+        assert!(Keymap::from_config(&config).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_names_and_keys() {
+        let config = overrides("launch = \"l\"");
+        assert_eq!(
+            Keymap::from_config(&config).err().as_deref(),
+            Some("keys: unknown action \"launch\"")
+        );
+        let config = overrides("help = \"Hyper-h\"");
+        assert_eq!(
+            Keymap::from_config(&config).err().as_deref(),
+            Some("keys: invalid key \"Hyper-h\" for help")
+        );
+    }
+}
@@ -1,60 +1,442 @@
 use sysinfo::System;
+mod alerts;
+mod app;
+mod cgroups;
+mod config;
+mod connections;
+mod disks;
+mod events;
+mod fleet;
+mod inspect;
+mod keys;
+mod pinned;
+mod process_history;
 mod processes;
+mod remote;
+mod snapshot;
+mod sockets;
 mod system;
+mod terminal;
+mod theme;
+mod threads;
+mod topology;
 mod ui;
-use crate::processes::collect_processes;
-use crate::system::{collect_disks_stats, collect_system_stats};
-use crate::ui::draw_ui;
+use crate::alerts::AlertEngine;
+use crate::app::App;
+use crate::config::load_config;
+use crate::fleet::{FleetRow, FleetView};
+use crate::keys::{Action, Keymap};
+use crate::processes::parse_columns;
+use crate::remote::{run_agent, Address, RemoteClient};
+use crate::snapshot::{Collector, Snapshot};
+use crate::terminal::{install_panic_hook, resume, suspend, TerminalGuard};
+use crate::theme::resolve_theme;
+use crate::ui::{draw_fleet, draw_ui};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use signal_hook::consts::{SIGCONT, SIGHUP, SIGINT, SIGTERM, SIGTSTP};
+use signal_hook::iterator::Signals;
 use std::{
+    env,
     io::{self, Result},
-    time::Duration,
+    path::PathBuf,
+    process,
+    time::{Duration, Instant},
 };
-use tui::{backend::CrosstermBackend, Terminal};
-
-fn main() -> Result<()> {
-    // Setup terminal in raw mode
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
+use tui::{backend::CrosstermBackend, layout::Rect, Terminal};
+
+const TICK: Duration = Duration::from_millis(400);
+
+const USAGE: &str =
+    "Usage: resource-manager [--config <path>] [--agent <address> | --connect <address> | --fleet]
+Addresses are host:port for TCP or unix:<path> for a Unix socket";
+
+enum Mode {
+    Local,
+    // Serve snapshots to remote clients instead of showing the UI
+    Agent(Address),
+    // Show an agent's snapshots instead of this machine's
+    Connect(Address),
+    // List the agents from the config, opening one at a time
+    Fleet,
+}
+
+struct Args {
+    config_path: Option<PathBuf>,
+    mode: Mode,
+}
+
+fn parse_args() -> Args {
+    let mut config_path = None;
+    let mut mode = Mode::Local;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-c" | "--config" => match args.next() {
+                Some(path) => config_path = Some(PathBuf::from(path)),
+                None => {
+                    eprintln!("{} requires a path\n{}", arg, USAGE);
+                    process::exit(2);
+                }
+            },
+            "--agent" | "--connect" => {
+                let address = match args.next() {
+                    Some(address) => Address::parse(&address),
+                    None => {
+                        eprintln!("{} requires an address\n{}", arg, USAGE);
+                        process::exit(2);
+                    }
+                };
+                if !matches!(mode, Mode::Local) {
+                    eprintln!("--agent, --connect and --fleet are exclusive\n{}", USAGE);
+                    process::exit(2);
+                }
+                mode = if arg == "--agent" {
+                    Mode::Agent(address)
+                } else {
+                    Mode::Connect(address)
+                };
+            }
+            "--fleet" => {
+                if !matches!(mode, Mode::Local) {
+                    eprintln!("--agent, --connect and --fleet are exclusive\n{}", USAGE);
+                    process::exit(2);
+                }
+                mode = Mode::Fleet;
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => {
+                eprintln!("unknown argument {}\n{}", arg, USAGE);
+                process::exit(2);
+            }
+        }
+    }
+    Args { config_path, mode }
+}
+
+// Where snapshots come from: this machine, or an agent elsewhere
+enum Source {
+    Local(Box<Collector>),
+    Remote(RemoteClient),
+}
+
+// One machine's latest data with the UI state and alerts that go with it
+struct Host {
+    source: Source,
+    app: App,
+    alerts: AlertEngine,
+    snapshot: Snapshot,
+}
+
+impl Host {
+    // `first` is the first tick, whose local snapshot was taken at startup
+    fn refresh(&mut self, now: Instant, first: bool) {
+        let fresh = match &mut self.source {
+            Source::Local(collector) => {
+                if !first {
+                    collector.cpu_mode = self.app.cpu_mode;
+                    self.snapshot = collector.collect();
+                }
+                true
+            }
+            Source::Remote(client) => {
+                let received = client.poll();
+                if let Some(host) = &client.status.host {
+                    self.alerts.set_host(host);
+                }
+                self.app.remote = Some(client.status.clone());
+                match received {
+                    Some(mut received) => {
+                        received.set_cpu_mode(self.app.cpu_mode);
+                        self.snapshot = received;
+                        true
+                    }
+                    None => false,
+                }
+            }
+        };
+        // History and alerts only advance on new data, so a stalled
+        // agent does not repeat its last snapshot
+        if fresh {
+            self.app.sort(&mut self.snapshot.processes);
+            self.app.record(&self.snapshot, now);
+            self.alerts.evaluate(&self.snapshot, now);
+        }
+    }
+
+    fn fleet_row(&self, index: usize) -> Option<FleetRow> {
+        let status = self.app.remote.as_ref()?;
+        Some(FleetRow::new(index, status, &self.snapshot, &self.alerts))
+    }
+}
+
+fn main() {
+    // Load the config before touching the terminal so errors stay readable
+    let args = parse_args();
+    let config = match load_config(args.config_path.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("resource-manager: {}", e);
+            process::exit(1);
+        }
+    };
+    let theme = match resolve_theme(config.theme.as_deref(), &config.themes) {
+        Ok(theme) => theme,
+        Err(e) => {
+            eprintln!("resource-manager: {}", e);
+            process::exit(1);
+        }
+    };
+    let keymap = match Keymap::from_config(&config.keys) {
+        Ok(keymap) => keymap,
+        Err(e) => {
+            eprintln!("resource-manager: {}", e);
+            process::exit(1);
+        }
+    };
+    let columns = match parse_columns(&config.processes.columns) {
+        Ok(columns) => columns,
+        Err(e) => {
+            eprintln!("resource-manager: {}", e);
+            process::exit(1);
+        }
+    };
+    let smaps = columns.iter().any(|c| c.needs_smaps());
+    let in_fleet = matches!(args.mode, Mode::Fleet);
+    let sources = match args.mode {
+        Mode::Local => vec![Source::Local(Box::new(Collector::new(
+            config.disks.clone(),
+            smaps,
+            config.processes.cpu_mode,
+        )))],
+        Mode::Agent(address) => {
+            let collector = Collector::new(config.disks.clone(), smaps, config.processes.cpu_mode);
+            if let Err(e) = run_agent(&address, collector, TICK) {
+                eprintln!("resource-manager: agent on {}: {}", address, e);
+                process::exit(1);
+            }
+            return;
+        }
+        Mode::Connect(address) => vec![Source::Remote(RemoteClient::spawn(address))],
+        Mode::Fleet if config.fleet.hosts.is_empty() => {
+            eprintln!("resource-manager: fleet: no hosts listed under [fleet]");
+            process::exit(1);
+        }
+        Mode::Fleet => config
+            .fleet
+            .hosts
+            .iter()
+            .map(|address| Source::Remote(RemoteClient::spawn(Address::parse(address))))
+            .collect(),
+    };
+    let history_len = config.processes.history_len(TICK);
+    let mut hosts: Vec<Host> = sources
+        .into_iter()
+        .map(|mut source| {
+            let mut app = App::new(
+                &config,
+                theme.clone(),
+                keymap.clone(),
+                columns.clone(),
+                history_len,
+            );
+            // Alerts name a remote host once its agent has said hello
+            let host = match &source {
+                Source::Local(_) => System::host_name().unwrap_or_default(),
+                Source::Remote(client) => client.status.address.clone(),
+            };
+            if in_fleet {
+                app.in_fleet = true;
+                app.events.set_host(host.clone());
+            }
+            // A remote host shows empty until its first snapshot arrives
+            let snapshot = match &mut source {
+                Source::Local(collector) => collector.collect(),
+                Source::Remote(_) => Snapshot::default(),
+            };
+            Host {
+                source,
+                app,
+                alerts: AlertEngine::new(config.alerts.clone(), host),
+                snapshot,
+            }
+        })
+        .collect();
+    let fleet = in_fleet.then(FleetView::new);
+
+    install_panic_hook();
+    if let Err(e) = run(&mut hosts, fleet) {
+        // The terminal guard has been dropped by now, so this is readable
+        eprintln!("resource-manager: {}", e);
+        process::exit(1);
+    }
+}
+
+// Runs the UI for one host, or for the fleet view with `fleet` set, where
+// each host opens into the single-host UI and Esc goes back
+fn run(hosts: &mut [Host], mut fleet: Option<FleetView>) -> Result<()> {
+    // Termination signals end the loop like `q` does, so the guard restores
+    // the terminal on the way out. SIGTSTP is caught so the terminal can be
+    // restored before stopping, and SIGCONT to take it back afterwards.
+    let mut signals = Signals::new([SIGTERM, SIGINT, SIGHUP, SIGTSTP, SIGCONT])?;
+    let _guard = TerminalGuard::enter()?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
-    let mut sys = System::new_all();
+    let mut last_tick: Option<Instant> = None;
+    // The host on screen; None while the fleet view shows
+    let mut focused = if fleet.is_some() { None } else { Some(0) };
+    let mut mouse_capture = true;
 
     loop {
-        // Refresh data
-        sys.refresh_all();
-        // Collect stats and processes
-        let stats = collect_system_stats(&mut sys);
-        let processes = collect_processes(&sys);
-        let disks = collect_disks_stats();
+        let mut quit = false;
+        for signal in signals.pending() {
+            match signal {
+                // The SIGCONT that continues us is queued behind this, so
+                // resuming is left to that branch
+                SIGTSTP => suspend()?,
+                // Also covers being stopped with SIGSTOP, which cannot be caught
+                SIGCONT => {
+                    resume(mouse_capture)?;
+                    terminal.clear()?;
+                }
+                _ => quit = true,
+            }
+        }
+        if quit {
+            break;
+        }
+
+        // Refresh data once per tick; key presses in between only redraw.
+        // Every host keeps refreshing while another is open, so their
+        // history and alerts stay current.
+        if last_tick.is_none_or(|t| t.elapsed() >= TICK) {
+            let now = Instant::now();
+            for host in hosts.iter_mut() {
+                host.refresh(now, last_tick.is_none());
+            }
+            last_tick = Some(now);
+        }
+        let rows = match &fleet {
+            Some(view) => view.visible(
+                hosts
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, host)| host.fleet_row(i))
+                    .collect(),
+            ),
+            None => Vec::new(),
+        };
 
         // Draw terminal
-        terminal.draw(|frame| {
-            draw_ui(frame, &stats, &disks, &processes);
+        terminal.draw(|frame| match (focused, &mut fleet) {
+            (Some(i), _) => {
+                let host = &mut hosts[i];
+                draw_ui(frame, &mut host.app, &host.snapshot, &host.alerts);
+            }
+            (None, Some(view)) => {
+                let app = &hosts[0].app;
+                let total = hosts.len();
+                draw_fleet(
+                    frame,
+                    view,
+                    &rows,
+                    total,
+                    &app.theme,
+                    &app.keymap,
+                    mouse_capture,
+                );
+            }
+            (None, None) => {}
         })?;
 
-        // Check if user pressed 'q' or `ESC` to quit
-        if crossterm::event::poll(Duration::from_millis(400))? {
-            if let Event::Key(key_event) = event::read()? {
-                if key_event.code == KeyCode::Char('q') || key_event.code == KeyCode::Esc {
-                    break;
+        let timeout = TICK.saturating_sub(last_tick.map_or(TICK, |t| t.elapsed()));
+        if !crossterm::event::poll(timeout)? {
+            continue;
+        }
+        match event::read()? {
+            Event::Key(key_event) => {
+                // While a search is being typed, keys are text rather than bindings
+                let typed = match (focused, &mut fleet) {
+                    (Some(i), _) => hosts[i].app.edit_search(&key_event),
+                    (None, Some(view)) => view.edit_search(&key_event),
+                    (None, None) => false,
+                };
+                if typed {
+                    continue;
+                }
+                match hosts[0].app.keymap.action(&key_event) {
+                    Some(Action::Quit) => break,
+                    // Back leaves an open host for the fleet, and quits only
+                    // when there is nothing left to close
+                    Some(Action::Back) => {
+                        let closed = match (focused, &mut fleet) {
+                            (Some(i), _) => hosts[i].app.back(),
+                            (None, Some(view)) => view.back(),
+                            (None, None) => false,
+                        };
+                        if !closed {
+                            match (focused, &fleet) {
+                                (Some(_), Some(_)) => focused = None,
+                                _ => break,
+                            }
+                        }
+                    }
+                    Some(Action::ToggleMouse) => {
+                        // Releasing the mouse lets the terminal select and copy text again
+                        mouse_capture = !mouse_capture;
+                        for host in hosts.iter_mut() {
+                            host.app.mouse_capture = mouse_capture;
+                        }
+                        if mouse_capture {
+                            execute!(terminal.backend_mut(), EnableMouseCapture)?;
+                        } else {
+                            execute!(terminal.backend_mut(), DisableMouseCapture)?;
+                        }
+                    }
+                    Some(Action::Suspend) => {
+                        // Raw mode turns Ctrl-Z into a key press instead of
+                        // SIGTSTP. The SIGCONT branch above takes the terminal
+                        // back on the next pass.
+                        suspend()?;
+                    }
+                    Some(action) => match (focused, &mut fleet) {
+                        (Some(i), _) => {
+                            let host = &mut hosts[i];
+                            host.app.apply(action, &mut host.snapshot);
+                        }
+                        (None, Some(view)) => {
+                            if let Some(opened) = view.apply(action, &rows) {
+                                focused = Some(opened);
+                            }
+                        }
+                        (None, None) => {}
+                    },
+                    None => {}
                 }
             }
+            Event::Mouse(mouse_event) => match (focused, &mut fleet) {
+                (Some(i), _) => {
+                    let host = &mut hosts[i];
+                    host.app
+                        .handle_mouse(mouse_event, &mut host.snapshot.processes);
+                }
+                (None, Some(view)) => {
+                    if let Some(opened) = view.handle_mouse(mouse_event, &rows) {
+                        focused = Some(opened);
+                    }
+                }
+                (None, None) => {}
+            },
+            // Resize the buffers right away rather than on the next tick,
+            // which also clears any artefacts the terminal left behind
+            Event::Resize(width, height) => terminal.resize(Rect::new(0, 0, width, height))?,
+            _ => {}
         }
     }
-
-    // Cleanup
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
     Ok(())
 }
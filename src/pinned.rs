@@ -0,0 +1,269 @@
+use crate::app::push_history;
+use crate::processes::{CpuMode, ProcessInfo};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use sysinfo::Pid;
+
+// How long the "restarted" marker stays highlighted after a PID change
+pub const RESTART_MARKER: Duration = Duration::from_secs(60);
+
+// Configured as `{ pid = 1 }`, `{ name = "nginx" }` or `{ cmdline = "*gunicorn*" }`.
+// Name and cmdline patterns accept `*` and `?` wildcards.
+#[derive(Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PinMatcher {
+    Pid(u32),
+    Name(String),
+    Cmdline(String),
+}
+
+impl PinMatcher {
+    pub fn matches(&self, p: &ProcessInfo) -> bool {
+        match self {
+            PinMatcher::Pid(pid) => p.pid.as_u32() == *pid,
+            PinMatcher::Name(pattern) => glob_match(pattern, &p.name),
+            PinMatcher::Cmdline(pattern) => glob_match(pattern, &p.cmd),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            PinMatcher::Pid(pid) => format!("pid {}", pid),
+            PinMatcher::Name(pattern) => pattern.clone(),
+            PinMatcher::Cmdline(pattern) => format!("cmd {}", pattern),
+        }
+    }
+}
+
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+pub struct PinnedProcess {
+    pub matcher: PinMatcher,
+    pub pid: Option<Pid>,
+    pub previous_pid: Option<Pid>,
+    pub restarted_at: Option<Instant>,
+    pub restarts: u32,
+    pub matches: usize,
+    // PIDs that matched on the previous refresh
+    matched_pids: Vec<Pid>,
+    // Percentage of one core whatever the CpuMode, so a toggle does not mix
+    // both in the history
+    pub cpu: f32,
+    pub memory: u64,
+    // CPU in hundredths of a percent, memory in bytes
+    pub cpu_history: VecDeque<u64>,
+    pub memory_history: VecDeque<u64>,
+}
+
+impl PinnedProcess {
+    pub fn new(matcher: PinMatcher) -> PinnedProcess {
+        PinnedProcess {
+            matcher,
+            pid: None,
+            previous_pid: None,
+            restarted_at: None,
+            restarts: 0,
+            matches: 0,
+            matched_pids: Vec::new(),
+            cpu: 0.0,
+            memory: 0,
+            cpu_history: VecDeque::new(),
+            memory_history: VecDeque::new(),
+        }
+    }
+
+    pub fn recently_restarted(&self, now: Instant) -> bool {
+        match self.restarted_at {
+            Some(at) => now.duration_since(at) < RESTART_MARKER,
+            None => false,
+        }
+    }
+
+    // `cpu_mode` and `cpus` are what the processes' CPU figures were scaled by
+    pub fn update(
+        &mut self,
+        processes: &[ProcessInfo],
+        cpu_mode: CpuMode,
+        cpus: usize,
+        now: Instant,
+    ) {
+        let matching: Vec<&ProcessInfo> = processes
+            .iter()
+            .filter(|p| !p.is_thread && self.matcher.matches(p))
+            .collect();
+        self.matches = matching.len();
+
+        // Stay on the tracked PID while it lives; otherwise follow the oldest
+        // match, which for daemons with workers is the parent.
+        let current = match self.pid {
+            Some(pid) => matching.iter().find(|p| p.pid == pid).copied(),
+            None => None,
+        };
+        let current = current.or_else(|| matching.iter().min_by_key(|p| p.start_time).copied());
+
+        match current {
+            Some(p) => {
+                // Moving to a process that was already running, such as a
+                // sibling worker when the tracked one exits, is no restart
+                if let Some(old) = self.pid.or(self.previous_pid) {
+                    if old != p.pid && !self.matched_pids.contains(&p.pid) {
+                        self.previous_pid = Some(old);
+                        self.restarted_at = Some(now);
+                        self.restarts += 1;
+                    }
+                }
+                self.pid = Some(p.pid);
+                self.cpu = cpu_mode.unscale(p.cpu, cpus);
+                self.memory = p.memory;
+            }
+            None => {
+                if self.pid.is_some() {
+                    self.previous_pid = self.pid;
+                }
+                self.pid = None;
+                self.cpu = 0.0;
+                self.memory = 0;
+            }
+        }
+        self.matched_pids = matching.iter().map(|p| p.pid).collect();
+        push_history(&mut self.cpu_history, (self.cpu * 100.0) as u64);
+        push_history(&mut self.memory_history, self.memory);
+    }
+}
+
+pub struct PinnedList {
+    pub entries: Vec<PinnedProcess>,
+}
+
+impl PinnedList {
+    pub fn new(matchers: Vec<PinMatcher>) -> PinnedList {
+        PinnedList {
+            entries: matchers.into_iter().map(PinnedProcess::new).collect(),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        processes: &[ProcessInfo],
+        cpu_mode: CpuMode,
+        cpus: usize,
+        now: Instant,
+    ) {
+        for entry in self.entries.iter_mut() {
+            entry.update(processes, cpu_mode, cpus, now);
+        }
+    }
+
+    // Pins by name so the entry follows restarts; unpins if already tracked
+    pub fn toggle(
+        &mut self,
+        process: &ProcessInfo,
+        processes: &[ProcessInfo],
+        cpu_mode: CpuMode,
+        cpus: usize,
+        now: Instant,
+    ) {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.pid != Some(process.pid));
+        if self.entries.len() == before {
+            let mut entry = PinnedProcess::new(PinMatcher::Name(process.name.clone()));
+            entry.pid = Some(process.pid);
+            entry.update(processes, cpu_mode, cpus, now);
+            self.entries.push(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: usize, start_time: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid: Pid::from(pid),
+            parent: None,
+            name: String::from("worker"),
+            cmd: String::from("worker --serve"),
+            memory: 0,
+            virtual_memory: 0,
+            pss: None,
+            uss: None,
+            swap: None,
+            cpu: 0.0,
+            state: 'S',
+            uptime: 0,
+            start_time,
+            euid: None,
+            egid: None,
+            cgroup: None,
+            read_bytes: 0,
+            written_bytes: 0,
+            is_thread: false,
+        }
+    }
+
+    fn pin() -> PinnedProcess {
+        PinnedProcess::new(PinMatcher::Name(String::from("worker")))
+    }
+
+    #[test]
+    fn sibling_takeover_is_not_a_restart() {
+        let mut pin = pin();
+        let now = Instant::now();
+        pin.update(&[process(10, 1), process(11, 2)], CpuMode::Irix, 1, now);
+        assert_eq!(pin.pid, Some(Pid::from(10)));
+
+        pin.update(&[process(11, 2)], CpuMode::Irix, 1, now);
+        assert_eq!(pin.pid, Some(Pid::from(11)));
+        assert_eq!(pin.restarts, 0);
+        assert!(!pin.recently_restarted(now));
+    }
+
+    #[test]
+    fn restart_after_absence() {
+        let mut pin = pin();
+        let now = Instant::now();
+        pin.update(&[process(10, 1)], CpuMode::Irix, 1, now);
+        pin.update(&[], CpuMode::Irix, 1, now);
+        assert_eq!(pin.pid, None);
+        assert_eq!(pin.previous_pid, Some(Pid::from(10)));
+
+        pin.update(&[process(20, 5)], CpuMode::Irix, 1, now);
+        assert_eq!(pin.pid, Some(Pid::from(20)));
+        assert_eq!(pin.restarts, 1);
+        assert!(pin.recently_restarted(now));
+    }
+
+    #[test]
+    fn restart_between_refreshes() {
+        // Every matching process was replaced within one refresh
+        let mut pin = pin();
+        let now = Instant::now();
+        pin.update(&[process(10, 1), process(11, 2)], CpuMode::Irix, 1, now);
+        pin.update(&[process(30, 7), process(31, 8)], CpuMode::Irix, 1, now);
+        assert_eq!(pin.pid, Some(Pid::from(30)));
+        assert_eq!(pin.restarts, 1);
+    }
+}
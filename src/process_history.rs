@@ -0,0 +1,99 @@
+use crate::processes::{CpuMode, ProcessInfo};
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+use sysinfo::Pid;
+
+// One process's samples, newest last, with running peak and average since
+// it was first seen
+pub struct ProcessSeries {
+    // CPU in hundredths of a percent of one core whatever the CpuMode,
+    // memory (RSS) in bytes
+    pub cpu: VecDeque<u64>,
+    pub memory: VecDeque<u64>,
+    pub peak_cpu: f32,
+    pub peak_memory: u64,
+    pub since: Instant,
+    cpu_total: f64,
+    memory_total: f64,
+    samples: u64,
+}
+
+impl ProcessSeries {
+    fn new(now: Instant) -> ProcessSeries {
+        ProcessSeries {
+            cpu: VecDeque::new(),
+            memory: VecDeque::new(),
+            peak_cpu: 0.0,
+            peak_memory: 0,
+            since: now,
+            cpu_total: 0.0,
+            memory_total: 0.0,
+            samples: 0,
+        }
+    }
+
+    pub fn average_cpu(&self) -> f32 {
+        (self.cpu_total / self.samples.max(1) as f64) as f32
+    }
+
+    pub fn average_memory(&self) -> u64 {
+        (self.memory_total / self.samples.max(1) as f64) as u64
+    }
+}
+
+fn push(samples: &mut VecDeque<u64>, value: u64, len: usize) {
+    if samples.len() == len {
+        samples.pop_front();
+    }
+    samples.push_back(value);
+}
+
+// Samples for every process, keyed by PID and start time so a reused PID
+// starts a fresh series. Series of exited processes are dropped on the next
+// refresh, so memory stays bounded by the process count times `len`.
+pub struct ProcessHistory {
+    len: usize,
+    series: HashMap<(Pid, u64), ProcessSeries>,
+}
+
+impl ProcessHistory {
+    pub fn new(len: usize) -> ProcessHistory {
+        ProcessHistory {
+            len: len.max(1),
+            series: HashMap::new(),
+        }
+    }
+
+    // `cpu_mode` and `cpus` are what the processes' CPU figures were scaled by
+    pub fn update(
+        &mut self,
+        processes: &[ProcessInfo],
+        cpu_mode: CpuMode,
+        cpus: usize,
+        now: Instant,
+    ) {
+        let mut series = HashMap::with_capacity(processes.len());
+        // Threads share their process's memory, so only leaders are kept
+        for p in processes.iter().filter(|p| !p.is_thread) {
+            let key = (p.pid, p.start_time);
+            let mut entry = self
+                .series
+                .remove(&key)
+                .unwrap_or_else(|| ProcessSeries::new(now));
+            let cpu = cpu_mode.unscale(p.cpu, cpus);
+            push(&mut entry.cpu, (cpu * 100.0) as u64, self.len);
+            push(&mut entry.memory, p.memory, self.len);
+            entry.peak_cpu = entry.peak_cpu.max(cpu);
+            entry.peak_memory = entry.peak_memory.max(p.memory);
+            entry.cpu_total += cpu as f64;
+            entry.memory_total += p.memory as f64;
+            entry.samples += 1;
+            series.insert(key, entry);
+        }
+        self.series = series;
+    }
+
+    pub fn get(&self, p: &ProcessInfo) -> Option<&ProcessSeries> {
+        self.series.get(&(p.pid, p.start_time))
+    }
+}
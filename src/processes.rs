@@ -1,27 +1,447 @@
-use sysinfo::{Gid, Pid, System, Uid};
+use crate::cgroups::{read_process_cgroup, CgroupInfo};
+use crate::inspect::read_rollup;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use sysinfo::{Gid, Pid, ProcessStatus, System, ThreadKind, Uid};
 
+pub const PROC_ROOT: &str = "/proc";
+
+// Columns of the Processes table
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortColumn {
+    Pid,
+    Name,
+    Memory,
+    Cpu,
+    Uptime,
+    User,
+    Group,
+    Virtual,
+    Pss,
+    Uss,
+    Swap,
+    MemoryPercent,
+    State,
+    CpuHistory,
+    MemoryHistory,
+}
+
+// Every column with its config name, in the order `columns` lists them
+pub const ALL_COLUMNS: [(SortColumn, &str); 15] = [
+    (SortColumn::Pid, "pid"),
+    (SortColumn::Name, "name"),
+    (SortColumn::Memory, "rss"),
+    (SortColumn::Cpu, "cpu"),
+    (SortColumn::Uptime, "uptime"),
+    (SortColumn::User, "user"),
+    (SortColumn::Group, "group"),
+    (SortColumn::Virtual, "virtual"),
+    (SortColumn::Pss, "pss"),
+    (SortColumn::Uss, "uss"),
+    (SortColumn::Swap, "swap"),
+    (SortColumn::MemoryPercent, "mem%"),
+    (SortColumn::State, "state"),
+    (SortColumn::CpuHistory, "cpu_graph"),
+    (SortColumn::MemoryHistory, "rss_graph"),
+];
+
+pub const DEFAULT_COLUMNS: [SortColumn; 8] = [
+    SortColumn::Pid,
+    SortColumn::Name,
+    SortColumn::Memory,
+    SortColumn::Cpu,
+    SortColumn::State,
+    SortColumn::Uptime,
+    SortColumn::User,
+    SortColumn::Group,
+];
+
+impl SortColumn {
+    pub fn title(&self) -> &'static str {
+        match self {
+            SortColumn::Pid => "PID",
+            SortColumn::Name => "Name",
+            SortColumn::Memory => "RSS (MB)",
+            SortColumn::Cpu => "CPU",
+            SortColumn::Uptime => "Uptime (s)",
+            SortColumn::User => "EUID/EGID",
+            SortColumn::Group => "Group",
+            SortColumn::Virtual => "Virt (MB)",
+            SortColumn::Pss => "PSS (MB)",
+            SortColumn::Uss => "USS (MB)",
+            SortColumn::Swap => "Swap (MB)",
+            SortColumn::MemoryPercent => "Mem %",
+            SortColumn::State => "S",
+            SortColumn::CpuHistory => "CPU history",
+            SortColumn::MemoryHistory => "RSS history",
+        }
+    }
+
+    // Share of the table's width, relative to the other shown columns
+    pub fn weight(&self) -> u16 {
+        match self {
+            SortColumn::Pid => 8,
+            SortColumn::Name => 22,
+            SortColumn::Memory => 11,
+            SortColumn::Cpu => 9,
+            SortColumn::Uptime => 12,
+            SortColumn::User => 14,
+            SortColumn::Group => 24,
+            SortColumn::Virtual | SortColumn::Pss | SortColumn::Uss | SortColumn::Swap => 11,
+            SortColumn::MemoryPercent => 8,
+            SortColumn::State => 4,
+            SortColumn::CpuHistory | SortColumn::MemoryHistory => 14,
+        }
+    }
+
+    // Resource columns are most useful largest-first
+    pub fn descending_by_default(&self) -> bool {
+        !matches!(
+            self,
+            SortColumn::Pid
+                | SortColumn::Name
+                | SortColumn::User
+                | SortColumn::Group
+                | SortColumn::State
+        )
+    }
+
+    // The columns that need /proc/<pid>/smaps_rollup, which is slow enough
+    // to skip unless one of them is shown
+    pub fn needs_smaps(&self) -> bool {
+        matches!(self, SortColumn::Pss | SortColumn::Uss | SortColumn::Swap)
+    }
+}
+
+// How a process's CPU usage is expressed. Irix is a percentage of one
+// core, so a process running on every core of an 8-core machine shows 800%;
+// Solaris divides that by the core count so the whole machine is 100%.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CpuMode {
+    #[default]
+    Irix,
+    Solaris,
+}
+
+impl CpuMode {
+    pub fn toggled(&self) -> CpuMode {
+        match self {
+            CpuMode::Irix => CpuMode::Solaris,
+            CpuMode::Solaris => CpuMode::Irix,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            CpuMode::Irix => "Irix",
+            CpuMode::Solaris => "Solaris",
+        }
+    }
+
+    // Converts a percentage of one core, as sysinfo reports it
+    pub fn scale(&self, percent_of_core: f32, cpus: usize) -> f32 {
+        match self {
+            CpuMode::Irix => percent_of_core,
+            CpuMode::Solaris => percent_of_core / cpus.max(1) as f32,
+        }
+    }
+
+    // Back to a percentage of one core, the unit CPU history is kept in so a
+    // toggle does not mix both in one series
+    pub fn unscale(&self, percent: f32, cpus: usize) -> f32 {
+        match self {
+            CpuMode::Irix => percent,
+            CpuMode::Solaris => percent * cpus.max(1) as f32,
+        }
+    }
+}
+
+// `[processes]` in the config
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct ProcessesConfig {
+    pub columns: Vec<String>,
+    pub cpu_mode: CpuMode,
+    // How far back each process's CPU and memory history reaches
+    pub history_minutes: u64,
+}
+
+impl Default for ProcessesConfig {
+    fn default() -> ProcessesConfig {
+        ProcessesConfig {
+            columns: Vec::new(),
+            cpu_mode: CpuMode::default(),
+            history_minutes: 2,
+        }
+    }
+}
+
+impl ProcessesConfig {
+    // Samples per process history at one sample per refresh
+    pub fn history_len(&self, tick: Duration) -> usize {
+        (self.history_minutes * 60_000 / tick.as_millis().max(1) as u64).max(1) as usize
+    }
+}
+
+// Config column names, defaulting to the classic set
+pub fn parse_columns(names: &[String]) -> Result<Vec<SortColumn>, String> {
+    if names.is_empty() {
+        return Ok(DEFAULT_COLUMNS.to_vec());
+    }
+    names
+        .iter()
+        .map(|name| {
+            ALL_COLUMNS
+                .iter()
+                .find(|(_, n)| n.eq_ignore_ascii_case(name))
+                .map(|(column, _)| *column)
+                .ok_or_else(|| format!("processes: unknown column \"{}\"", name))
+        })
+        .collect()
+}
+
+// sysinfo's ids have no serde support, so they travel as plain numbers
+mod ids {
+    use super::*;
+
+    pub fn serialize_pid<S: Serializer>(pid: &Pid, s: S) -> Result<S::Ok, S::Error> {
+        pid.as_u32().serialize(s)
+    }
+
+    pub fn deserialize_pid<'de, D: Deserializer<'de>>(d: D) -> Result<Pid, D::Error> {
+        u32::deserialize(d).map(Pid::from_u32)
+    }
+
+    pub fn serialize_parent<S: Serializer>(pid: &Option<Pid>, s: S) -> Result<S::Ok, S::Error> {
+        pid.map(|pid| pid.as_u32()).serialize(s)
+    }
+
+    pub fn deserialize_parent<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Pid>, D::Error> {
+        Option::<u32>::deserialize(d).map(|pid| pid.map(Pid::from_u32))
+    }
+
+    pub fn serialize_uid<S: Serializer>(uid: &Option<Uid>, s: S) -> Result<S::Ok, S::Error> {
+        uid.as_ref().map(|uid| **uid).serialize(s)
+    }
+
+    pub fn deserialize_uid<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Uid>, D::Error> {
+        Option::<u32>::deserialize(d).map(|uid| uid.and_then(|n| Uid::try_from(n as usize).ok()))
+    }
+
+    pub fn serialize_gid<S: Serializer>(gid: &Option<Gid>, s: S) -> Result<S::Ok, S::Error> {
+        gid.map(|gid| *gid).serialize(s)
+    }
+
+    pub fn deserialize_gid<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Gid>, D::Error> {
+        Option::<u32>::deserialize(d).map(|gid| gid.and_then(|n| Gid::try_from(n as usize).ok()))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct ProcessInfo {
+    #[serde(
+        serialize_with = "ids::serialize_pid",
+        deserialize_with = "ids::deserialize_pid"
+    )]
     pub pid: Pid,
+    #[serde(
+        serialize_with = "ids::serialize_parent",
+        deserialize_with = "ids::deserialize_parent"
+    )]
+    pub parent: Option<Pid>,
     pub name: String,
+    pub cmd: String,
+    // Resident set size, which counts shared pages in every process using them
     pub memory: u64,
+    pub virtual_memory: u64,
+    // From smaps_rollup: None when not collected or not readable (another
+    // user's process without root)
+    pub pss: Option<u64>,
+    pub uss: Option<u64>,
+    pub swap: Option<u64>,
+    // Percentage in the collector's CpuMode
     pub cpu: f32,
+    // Single-letter state as ps(1) shows it (R, S, D, Z, T, ...)
+    pub state: char,
     pub uptime: u64,
+    pub start_time: u64,
+    #[serde(
+        serialize_with = "ids::serialize_uid",
+        deserialize_with = "ids::deserialize_uid"
+    )]
     pub euid: Option<Uid>,
+    #[serde(
+        serialize_with = "ids::serialize_gid",
+        deserialize_with = "ids::deserialize_gid"
+    )]
     pub egid: Option<Gid>,
+    pub cgroup: Option<CgroupInfo>,
+    pub read_bytes: u64,
+    pub written_bytes: u64,
+    pub is_thread: bool,
+}
+
+fn compare(a: &ProcessInfo, b: &ProcessInfo, column: SortColumn) -> Ordering {
+    match column {
+        SortColumn::Pid => a.pid.cmp(&b.pid),
+        SortColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        SortColumn::Memory | SortColumn::MemoryPercent | SortColumn::MemoryHistory => {
+            a.memory.cmp(&b.memory)
+        }
+        SortColumn::Virtual => a.virtual_memory.cmp(&b.virtual_memory),
+        SortColumn::Pss => a.pss.cmp(&b.pss),
+        SortColumn::Uss => a.uss.cmp(&b.uss),
+        SortColumn::Swap => a.swap.cmp(&b.swap),
+        SortColumn::Cpu | SortColumn::CpuHistory => a.cpu.total_cmp(&b.cpu),
+        SortColumn::Uptime => a.uptime.cmp(&b.uptime),
+        SortColumn::State => state_rank(a.state).cmp(&state_rank(b.state)),
+        SortColumn::User => a
+            .euid
+            .as_ref()
+            .map(|uid| **uid)
+            .cmp(&b.euid.as_ref().map(|uid| **uid)),
+        SortColumn::Group => {
+            let label = |p: &ProcessInfo| p.cgroup.as_ref().map(|c| c.label());
+            label(a).cmp(&label(b))
+        }
+    }
+}
+
+fn state_letter(status: ProcessStatus) -> char {
+    match status {
+        ProcessStatus::Run => 'R',
+        ProcessStatus::Sleep => 'S',
+        ProcessStatus::Idle => 'I',
+        ProcessStatus::UninterruptibleDiskSleep => 'D',
+        ProcessStatus::Zombie => 'Z',
+        ProcessStatus::Stop => 'T',
+        ProcessStatus::Tracing => 't',
+        ProcessStatus::Dead => 'X',
+        ProcessStatus::Wakekill => 'K',
+        ProcessStatus::Waking => 'W',
+        ProcessStatus::Parked => 'P',
+        ProcessStatus::LockBlocked => 'L',
+        ProcessStatus::Unknown(_) => '?',
+    }
+}
+
+// Sorting by state puts stuck and defunct processes first, then running ones
+fn state_rank(state: char) -> u8 {
+    match state {
+        'D' => 0,
+        'Z' => 1,
+        'R' => 2,
+        'T' | 't' => 3,
+        'S' => 4,
+        'I' => 5,
+        _ => 6,
+    }
+}
+
+// Tasks per state for the Processes title. Every entry is a thread, while
+// only the thread group leaders count as processes.
+#[derive(Default)]
+pub struct StateCounts {
+    pub running: usize,
+    pub sleeping: usize,
+    pub disk_sleep: usize,
+    pub stopped: usize,
+    pub zombie: usize,
+    pub threads: usize,
 }
 
-pub fn collect_processes(sys: &System) -> Vec<ProcessInfo> {
+impl StateCounts {
+    pub fn count(processes: &[ProcessInfo]) -> StateCounts {
+        let mut counts = StateCounts::default();
+        for p in processes {
+            counts.threads += 1;
+            if p.is_thread {
+                continue;
+            }
+            match p.state {
+                'R' => counts.running += 1,
+                'S' | 'I' => counts.sleeping += 1,
+                'D' => counts.disk_sleep += 1,
+                'T' | 't' => counts.stopped += 1,
+                'Z' => counts.zombie += 1,
+                _ => {}
+            }
+        }
+        counts
+    }
+}
+
+// Ties fall back to PID so rows keep a stable order between refreshes
+pub fn sort_processes(processes: &mut [ProcessInfo], column: SortColumn, descending: bool) {
+    processes.sort_by(|a, b| {
+        let ordering = compare(a, b, column);
+        let ordering = if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        ordering.then(a.pid.cmp(&b.pid))
+    });
+}
+
+pub fn collect_processes(sys: &System, smaps: bool, cpu_mode: CpuMode) -> Vec<ProcessInfo> {
     let mut process_info_vec: Vec<ProcessInfo> = Vec::new();
-    for (_, process) in sys.processes() {
+    // Threads share their process's cgroup, so it is read once per process
+    let mut leaders = HashMap::new();
+    for process in sys.processes().values() {
+        for task in process.tasks().into_iter().flatten() {
+            leaders.insert(*task, process.pid());
+        }
+    }
+    let mut cgroups: HashMap<Pid, Option<CgroupInfo>> = HashMap::new();
+    for process in sys.processes().values() {
+        let disk_usage = process.disk_usage();
+        // Threads share their process's address space, so only read it once
+        let is_thread = process.thread_kind() == Some(ThreadKind::Userland);
+        let owner = match leaders.get(&process.pid()) {
+            Some(leader) if is_thread => *leader,
+            _ => process.pid(),
+        };
+        let cgroup = cgroups
+            .entry(owner)
+            .or_insert_with(|| read_process_cgroup(owner.as_u32(), Path::new(PROC_ROOT)))
+            .clone();
+        let rollup = if smaps && !is_thread {
+            read_rollup(process.pid().as_u32(), Path::new(PROC_ROOT))
+        } else {
+            None
+        };
         process_info_vec.push(ProcessInfo {
             pid: process.pid(),
+            parent: process.parent(),
             name: process.name().to_string_lossy().to_string(),
+            cmd: process
+                .cmd()
+                .iter()
+                .map(|arg| arg.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(" "),
             memory: process.memory(),
-            cpu: process.cpu_usage(),
+            virtual_memory: process.virtual_memory(),
+            pss: rollup.as_ref().map(|r| r.pss),
+            uss: rollup.as_ref().map(|r| r.uss),
+            swap: rollup.as_ref().map(|r| r.swap),
+            cpu: cpu_mode.scale(process.cpu_usage(), sys.cpus().len()),
+            state: state_letter(process.status()),
             uptime: process.run_time(),
+            start_time: process.start_time(),
             euid: process.effective_user_id().cloned(),
             egid: process.effective_group_id(),
+            cgroup,
+            read_bytes: disk_usage.total_read_bytes,
+            written_bytes: disk_usage.total_written_bytes,
+            is_thread,
         })
     }
+    process_info_vec.sort_by_key(|p| p.pid);
     process_info_vec
 }
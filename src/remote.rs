@@ -0,0 +1,499 @@
+use crate::snapshot::{Collector, Snapshot};
+use serde::{Deserialize, Serialize};
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+use signal_hook::iterator::Signals;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::System;
+
+// Bumped whenever a message changes shape; agent and client must agree.
+// Optional fields added with #[serde(default)] do not count, since older
+// and newer peers still read each other's messages.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+// A client that stops reading is dropped rather than stalling the agent
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+// Lines queued per client; a client further behind skips snapshots
+const CLIENT_BACKLOG: usize = 2;
+// The agent sends every tick, so this long without a line means it is gone
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+// "host:port" for TCP, "unix:/path" for a Unix socket
+#[derive(Clone)]
+pub enum Address {
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+impl Address {
+    pub fn parse(address: &str) -> Address {
+        match address.strip_prefix("unix:") {
+            Some(path) => Address::Unix(PathBuf::from(path)),
+            None => Address::Tcp(address.to_string()),
+        }
+    }
+
+    fn connect(&self) -> io::Result<Stream> {
+        match self {
+            Address::Tcp(address) => TcpStream::connect(address).map(Stream::Tcp),
+            Address::Unix(path) => UnixStream::connect(path).map(Stream::Unix),
+        }
+    }
+
+    fn bind(&self) -> io::Result<Listener> {
+        match self {
+            Address::Tcp(address) => TcpListener::bind(address).map(Listener::Tcp),
+            Address::Unix(path) => {
+                // A socket file nobody answers on is left over from an agent
+                // that did not exit cleanly. Anything else at the path is
+                // left alone.
+                if let Ok(metadata) = fs::symlink_metadata(path) {
+                    if !metadata.file_type().is_socket() || UnixStream::connect(path).is_ok() {
+                        return Err(io::Error::from(ErrorKind::AddrInUse));
+                    }
+                    fs::remove_file(path)?;
+                }
+                UnixListener::bind(path).map(Listener::Unix)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Address::Tcp(address) => write!(f, "{}", address),
+            Address::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+enum Stream {
+    Tcp(TcpStream),
+    Unix(UnixStream),
+}
+
+impl Stream {
+    fn set_timeouts(&self, read: Option<Duration>, write: Option<Duration>) -> io::Result<()> {
+        match self {
+            Stream::Tcp(s) => s.set_read_timeout(read).and(s.set_write_timeout(write)),
+            Stream::Unix(s) => s.set_read_timeout(read).and(s.set_write_timeout(write)),
+        }
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match self {
+            Stream::Tcp(s) => s.set_nonblocking(nonblocking),
+            Stream::Unix(s) => s.set_nonblocking(nonblocking),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(s) => s.read(buf),
+            Stream::Unix(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(s) => s.write(buf),
+            Stream::Unix(s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(s) => s.flush(),
+            Stream::Unix(s) => s.flush(),
+        }
+    }
+}
+
+enum Listener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+
+impl Listener {
+    fn set_nonblocking(&self) -> io::Result<()> {
+        match self {
+            Listener::Tcp(l) => l.set_nonblocking(true),
+            Listener::Unix(l) => l.set_nonblocking(true),
+        }
+    }
+
+    // None once no more connections are waiting. Failures such as a client
+    // resetting before being accepted only affect that client, so they are
+    // logged rather than ending the agent.
+    fn accept(&self) -> Option<(Stream, String)> {
+        let accepted = match self {
+            Listener::Tcp(l) => l
+                .accept()
+                .map(|(s, peer)| (Stream::Tcp(s), peer.to_string())),
+            Listener::Unix(l) => l
+                .accept()
+                .map(|(s, _)| (Stream::Unix(s), String::from("local socket"))),
+        };
+        match accepted {
+            Ok(accepted) => Some(accepted),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => None,
+            Err(e) => {
+                eprintln!("resource-manager: accept failed: {}", e);
+                None
+            }
+        }
+    }
+}
+
+// One JSON object per line. The agent greets each client with a hello,
+// then sends a snapshot every tick.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum MessageRef<'a> {
+    Hello {
+        protocol: u32,
+        host: &'a str,
+        version: &'a str,
+    },
+    Snapshot {
+        snapshot: &'a Snapshot,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    Hello {
+        protocol: u32,
+        host: String,
+        version: String,
+    },
+    Snapshot {
+        snapshot: Box<Snapshot>,
+    },
+}
+
+fn encode(message: &MessageRef) -> io::Result<Vec<u8>> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+// Writes to one client on its own thread, so a client that stops reading
+// only holds up itself. A failed or timed out write ends the connection
+// rather than carrying on after half a line.
+fn spawn_writer(mut stream: Stream, peer: String, hello: Arc<Vec<u8>>) -> SyncSender<Arc<Vec<u8>>> {
+    let (tx, lines) = mpsc::sync_channel::<Arc<Vec<u8>>>(CLIENT_BACKLOG);
+    thread::spawn(move || {
+        let result = stream
+            .set_nonblocking(false)
+            .and(stream.set_timeouts(None, Some(WRITE_TIMEOUT)))
+            .and(stream.write_all(&hello))
+            .and_then(|()| lines.iter().try_for_each(|line| stream.write_all(&line)));
+        if let Err(e) = result {
+            eprintln!("resource-manager: dropped client {}: {}", peer, e);
+        }
+    });
+    tx
+}
+
+// The listening side of the agent, one refresh at a time
+struct Agent {
+    listener: Listener,
+    hello: Arc<Vec<u8>>,
+    clients: Vec<SyncSender<Arc<Vec<u8>>>>,
+}
+
+impl Agent {
+    fn bind(address: &Address) -> io::Result<Agent> {
+        let listener = address.bind()?;
+        listener.set_nonblocking()?;
+        let host = System::host_name().unwrap_or_default();
+        let hello = encode(&MessageRef::Hello {
+            protocol: PROTOCOL_VERSION,
+            host: &host,
+            version: env!("CARGO_PKG_VERSION"),
+        })?;
+        Ok(Agent {
+            listener,
+            hello: Arc::new(hello),
+            clients: Vec::new(),
+        })
+    }
+
+    // Greets any waiting clients, then queues the snapshot for everyone
+    fn serve(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        while let Some((stream, peer)) = self.listener.accept() {
+            eprintln!("resource-manager: client connected from {}", peer);
+            let writer = spawn_writer(stream, peer, Arc::clone(&self.hello));
+            self.clients.push(writer);
+        }
+
+        // Serialized once however many clients there are
+        let line = Arc::new(encode(&MessageRef::Snapshot { snapshot })?);
+        // A client whose writer has given up is dropped; one that is behind
+        // misses this snapshot and gets a later one
+        self.clients
+            .retain(|client| match client.try_send(Arc::clone(&line)) {
+                Ok(()) | Err(TrySendError::Full(_)) => true,
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+        Ok(())
+    }
+}
+
+// Serves snapshots until SIGTERM, SIGINT or SIGHUP
+pub fn run_agent(address: &Address, mut collector: Collector, tick: Duration) -> io::Result<()> {
+    let mut signals = Signals::new([SIGTERM, SIGINT, SIGHUP])?;
+    let mut agent = Agent::bind(address)?;
+    eprintln!("resource-manager: agent listening on {}", address);
+
+    while signals.pending().next().is_none() {
+        let started = Instant::now();
+        agent.serve(&collector.collect())?;
+        thread::sleep(tick.saturating_sub(started.elapsed()));
+    }
+
+    if let Address::Unix(path) = address {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
+
+// What the UI shows about the connection in the tab bar
+#[derive(Clone)]
+pub struct RemoteStatus {
+    pub address: String,
+    // From the agent's hello
+    pub host: Option<String>,
+    pub connected: bool,
+    // When the last snapshot arrived
+    pub last_seen: Option<Instant>,
+    // Why the last connection attempt failed or ended
+    pub error: Option<String>,
+}
+
+enum Update {
+    Connected(String),
+    Snapshot(Box<Snapshot>),
+    Disconnected(String),
+}
+
+// Reads from an agent on a background thread, reconnecting whenever the
+// connection fails, so the UI never blocks on the network
+pub struct RemoteClient {
+    pub status: RemoteStatus,
+    updates: Receiver<Update>,
+}
+
+impl RemoteClient {
+    pub fn spawn(address: Address) -> RemoteClient {
+        let (tx, updates) = mpsc::channel();
+        let status = RemoteStatus {
+            address: address.to_string(),
+            host: None,
+            connected: false,
+            last_seen: None,
+            error: None,
+        };
+        thread::spawn(move || loop {
+            let error = match receive(&address, &tx) {
+                Ok(()) => String::from("agent closed the connection"),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    format!("no data for {}s", READ_TIMEOUT.as_secs())
+                }
+                Err(e) => e.to_string(),
+            };
+            // The client was dropped, so nobody is listening any more
+            if tx.send(Update::Disconnected(error)).is_err() {
+                return;
+            }
+            thread::sleep(RETRY_DELAY);
+        });
+        RemoteClient { status, updates }
+    }
+
+    // The newest snapshot received since the last call, if any
+    pub fn poll(&mut self) -> Option<Snapshot> {
+        let mut latest = None;
+        while let Ok(update) = self.updates.try_recv() {
+            match update {
+                Update::Connected(host) => {
+                    self.status.host = Some(host);
+                    self.status.connected = true;
+                    self.status.error = None;
+                }
+                Update::Snapshot(snapshot) => {
+                    self.status.last_seen = Some(Instant::now());
+                    latest = Some(*snapshot);
+                }
+                Update::Disconnected(error) => {
+                    self.status.connected = false;
+                    self.status.error = Some(error);
+                }
+            }
+        }
+        latest
+    }
+}
+
+// One connection, until it fails or the agent hangs up
+fn receive(address: &Address, tx: &Sender<Update>) -> io::Result<()> {
+    let stream = address.connect()?;
+    stream.set_timeouts(Some(READ_TIMEOUT), None)?;
+    let mut lines = BufReader::new(stream).lines();
+
+    let hello = match lines.next() {
+        Some(line) => serde_json::from_str(&line?)?,
+        None => return Ok(()),
+    };
+    match hello {
+        Message::Hello { protocol, host, .. } if protocol == PROTOCOL_VERSION => {
+            if tx.send(Update::Connected(host)).is_err() {
+                return Ok(());
+            }
+        }
+        Message::Hello {
+            protocol, version, ..
+        } => {
+            return Err(io::Error::other(format!(
+                "agent {} speaks protocol {}, expected {}",
+                version, protocol, PROTOCOL_VERSION
+            )))
+        }
+        Message::Snapshot { .. } => {
+            return Err(io::Error::other("agent did not send a hello"));
+        }
+    }
+
+    for line in lines {
+        if let Message::Snapshot { snapshot } = serde_json::from_str(&line?)? {
+            if tx.send(Update::Snapshot(snapshot)).is_err() {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn socket_path(name: &str) -> PathBuf {
+        let path =
+            env::temp_dir().join(format!("resource-manager-{}-{}.sock", name, process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    // Runs `receive` against `address` on a thread, as RemoteClient does
+    fn start_client(address: &Address) -> (Receiver<Update>, thread::JoinHandle<io::Result<()>>) {
+        let (tx, rx) = mpsc::channel();
+        let address = address.clone();
+        let handle = thread::spawn(move || receive(&address, &tx));
+        (rx, handle)
+    }
+
+    #[test]
+    fn hello_and_snapshot_round_trip() {
+        let path = socket_path("round-trip");
+        let address = Address::Unix(path.clone());
+        let mut agent = Agent::bind(&address).unwrap();
+        let (updates, client) = start_client(&address);
+
+        let mut snapshot = Snapshot::default();
+        snapshot.stats.cpu_global_usage = 42.5;
+        snapshot.stats.host_name = Some(String::from("test-host"));
+        // The client connects in the background, so keep serving until the
+        // first snapshot comes through
+        let mut connected = None;
+        let mut received = None;
+        for _ in 0..100 {
+            agent.serve(&snapshot).unwrap();
+            while let Ok(update) = updates.try_recv() {
+                match update {
+                    Update::Connected(host) => connected = Some(host),
+                    Update::Snapshot(snapshot) => received = Some(snapshot),
+                    Update::Disconnected(error) => panic!("disconnected: {}", error),
+                }
+            }
+            if received.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        assert_eq!(connected, Some(System::host_name().unwrap_or_default()));
+        let received = received.expect("no snapshot received");
+        assert_eq!(received.stats.cpu_global_usage, 42.5);
+        assert_eq!(received.stats.host_name.as_deref(), Some("test-host"));
+
+        // Closing the agent ends the connection cleanly
+        drop(agent);
+        assert!(client.join().unwrap().is_ok());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn rejects_other_protocol_versions() {
+        let path = socket_path("mismatch");
+        let listener = UnixListener::bind(&path).unwrap();
+        let (_, client) = start_client(&Address::Unix(path.clone()));
+
+        let (mut stream, _) = listener.accept().unwrap();
+        let hello = encode(&MessageRef::Hello {
+            protocol: PROTOCOL_VERSION + 1,
+            host: "future",
+            version: "9.9.9",
+        })
+        .unwrap();
+        stream.write_all(&hello).unwrap();
+
+        let error = client.join().unwrap().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "agent 9.9.9 speaks protocol {}, expected {}",
+                PROTOCOL_VERSION + 1,
+                PROTOCOL_VERSION
+            )
+        );
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn bind_leaves_other_files_alone() {
+        let path = socket_path("regular-file");
+        fs::write(&path, "keep me").unwrap();
+        let error = Address::Unix(path.clone()).bind().err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::AddrInUse);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "keep me");
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn bind_replaces_stale_socket() {
+        let path = socket_path("stale");
+        drop(UnixListener::bind(&path).unwrap());
+        assert!(Address::Unix(path.clone()).bind().is_ok());
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::cgroups::{collect_cgroup_groups, CgroupGroup, CGROUP_ROOT};
+use crate::disks::{collect_disks, DiskFilter, DiskInfo, SYS_BLOCK_ROOT};
+use crate::processes::{collect_processes, CpuMode, ProcessInfo};
+use crate::system::{
+    collect_network_stats, collect_pressure_stats, collect_sensors, collect_system_stats,
+    read_cpu_stat, read_meminfo, CpuBreakdown, CpuStat, CpuTimes, MemInfo, NetworkInterface,
+    PressureStats, SensorReading, SystemStats, MEMINFO_PATH, PRESSURE_ROOT, STAT_PATH,
+};
+use crate::topology::{CpuTopology, TopologyReader, CPU_SYS_ROOT};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Instant;
+use sysinfo::{Components, Networks, ProcessRefreshKind, ProcessesToUpdate, RefreshKind, System};
+
+// Everything collected in one refresh of the main loop, which is also what
+// an agent sends to remote clients
+#[derive(Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub stats: SystemStats,
+    pub meminfo: Option<MemInfo>,
+    // Where CPU time went since the previous refresh, overall and per core
+    // in the order of stats.cpu_cores; None outside Linux
+    pub cpu_breakdown: Option<CpuBreakdown>,
+    pub core_breakdown: Vec<CpuBreakdown>,
+    pub topology: CpuTopology,
+    pub disks: Vec<DiskInfo>,
+    pub processes: Vec<ProcessInfo>,
+    pub groups: Vec<CgroupGroup>,
+    pub pressure: PressureStats,
+    pub networks: Vec<NetworkInterface>,
+    pub sensors: Vec<SensorReading>,
+    // How process and group CPU figures are scaled
+    pub cpu_mode: CpuMode,
+}
+
+impl Snapshot {
+    // Rescales process and group CPU figures, for snapshots collected in
+    // another mode such as those from an agent
+    pub fn set_cpu_mode(&mut self, mode: CpuMode) {
+        if self.cpu_mode == mode {
+            return;
+        }
+        let cpus = self.stats.cpu_cores.len().max(1) as f32;
+        let factor = match mode {
+            CpuMode::Irix => cpus,
+            CpuMode::Solaris => 1.0 / cpus,
+        };
+        for p in &mut self.processes {
+            p.cpu *= factor;
+        }
+        for group in &mut self.groups {
+            group.cpu *= factor;
+        }
+        self.cpu_mode = mode;
+    }
+}
+
+// Owns the sysinfo handles that compute deltas between refreshes
+pub struct Collector {
+    sys: System,
+    networks: Networks,
+    components: Components,
+    topology: TopologyReader,
+    disk_filter: DiskFilter,
+    // Whether to read smaps_rollup for PSS/USS/swap
+    smaps: bool,
+    // How process CPU usage is scaled, switched from the UI
+    pub cpu_mode: CpuMode,
+    // /proc/stat counters from the previous refresh
+    cpu_stat: Option<CpuStat>,
+    last_refresh: Instant,
+}
+
+// The first refresh has no baseline and reports the averages since boot
+fn cpu_breakdowns(
+    previous: Option<&CpuStat>,
+    current: &CpuStat,
+) -> (CpuBreakdown, Vec<CpuBreakdown>) {
+    let zero = CpuTimes::default();
+    let total = CpuBreakdown::between(previous.map_or(&zero, |p| &p.total), &current.total);
+    let cores = current
+        .cores
+        .iter()
+        .enumerate()
+        .map(|(i, core)| {
+            let before = previous.and_then(|p| p.cores.get(i)).unwrap_or(&zero);
+            CpuBreakdown::between(before, core)
+        })
+        .collect();
+    (total, cores)
+}
+
+impl Collector {
+    pub fn new(disk_filter: DiskFilter, smaps: bool, cpu_mode: CpuMode) -> Collector {
+        Collector {
+            sys: System::new_all(),
+            networks: Networks::new_with_refreshed_list(),
+            components: Components::new_with_refreshed_list(),
+            topology: TopologyReader::new(Path::new(CPU_SYS_ROOT)),
+            disk_filter,
+            smaps,
+            cpu_mode,
+            cpu_stat: None,
+            last_refresh: Instant::now(),
+        }
+    }
+
+    pub fn collect(&mut self) -> Snapshot {
+        let sys = &mut self.sys;
+        // refresh_all() keeps exited processes around, so refresh them separately
+        sys.refresh_specifics(RefreshKind::everything().without_processes());
+        sys.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::everything(),
+        );
+        self.networks.refresh(true);
+        self.components.refresh(true);
+        let interval = self.last_refresh.elapsed();
+        self.last_refresh = Instant::now();
+
+        let stats = collect_system_stats(sys);
+        let processes = collect_processes(sys, self.smaps, self.cpu_mode);
+        let disks = collect_disks(&self.disk_filter, Path::new(SYS_BLOCK_ROOT));
+        let groups = collect_cgroup_groups(&processes, Path::new(CGROUP_ROOT));
+        let pressure = collect_pressure_stats(Path::new(PRESSURE_ROOT));
+        let cpu_stat = read_cpu_stat(Path::new(STAT_PATH));
+        let (cpu_breakdown, core_breakdown) = match &cpu_stat {
+            Some(current) => {
+                let (total, cores) = cpu_breakdowns(self.cpu_stat.as_ref(), current);
+                (Some(total), cores)
+            }
+            None => (None, Vec::new()),
+        };
+        self.cpu_stat = cpu_stat;
+
+        Snapshot {
+            stats,
+            meminfo: read_meminfo(Path::new(MEMINFO_PATH)),
+            cpu_breakdown,
+            core_breakdown,
+            topology: self.topology.collect(&self.sys),
+            disks,
+            processes,
+            groups,
+            pressure,
+            networks: collect_network_stats(&self.networks, interval),
+            sensors: collect_sensors(&self.components),
+            cpu_mode: self.cpu_mode,
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+pub struct Socket {
+    pub protocol: &'static str,
+    pub local: String,
+    pub remote: String,
+    pub state: &'static str,
+    pub inode: u64,
+}
+
+// Socket states as numbered in include/net/tcp_states.h
+fn tcp_state(code: u8) -> &'static str {
+    match code {
+        0x01 => "ESTABLISHED",
+        0x02 => "SYN_SENT",
+        0x03 => "SYN_RECV",
+        0x04 => "FIN_WAIT1",
+        0x05 => "FIN_WAIT2",
+        0x06 => "TIME_WAIT",
+        0x07 => "CLOSE",
+        0x08 => "CLOSE_WAIT",
+        0x09 => "LAST_ACK",
+        0x0A => "LISTEN",
+        0x0B => "CLOSING",
+        _ => "UNKNOWN",
+    }
+}
+
+// UDP reuses the TCP numbering, but an unconnected socket is just bound
+fn udp_state(code: u8) -> &'static str {
+    match code {
+        0x01 => "ESTABLISHED",
+        0x07 => "UNCONN",
+        _ => "UNKNOWN",
+    }
+}
+
+// The kernel prints each 32-bit word of the address in host byte order,
+// e.g. 0100007F:1F90 is 127.0.0.1:8080 on little-endian machines
+fn parse_address(s: &str) -> Option<String> {
+    let (address, port) = s.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let mut bytes = Vec::with_capacity(16);
+    for i in (0..address.len()).step_by(8) {
+        let word = u32::from_str_radix(address.get(i..i + 8)?, 16).ok()?;
+        bytes.extend_from_slice(&word.to_ne_bytes());
+    }
+    match bytes.len() {
+        4 => Some(format!(
+            "{}:{}",
+            Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]),
+            port
+        )),
+        16 => {
+            let octets: [u8; 16] = bytes.try_into().ok()?;
+            Some(format!("[{}]:{}", Ipv6Addr::from(octets), port))
+        }
+        _ => None,
+    }
+}
+
+// One of /proc/net/{tcp,tcp6,udp,udp6}; a missing file (no IPv6) is empty
+fn read_inet(path: &Path, protocol: &'static str, udp: bool) -> Vec<Socket> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let code = u8::from_str_radix(fields.get(3)?, 16).ok()?;
+            Some(Socket {
+                protocol,
+                local: parse_address(fields.get(1)?)?,
+                remote: parse_address(fields.get(2)?)?,
+                state: if udp {
+                    udp_state(code)
+                } else {
+                    tcp_state(code)
+                },
+                inode: fields.get(9)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+// /proc/net/unix: Num RefCount Protocol Flags Type St Inode [Path]
+fn read_unix(path: &Path) -> Vec<Socket> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // __SO_ACCEPTCON in the flags marks a listening socket
+            let listening = u32::from_str_radix(fields.get(3)?, 16).ok()? & 0x10000 != 0;
+            let state = match (listening, *fields.get(5)?) {
+                (true, _) => "LISTEN",
+                (false, "01") => "UNCONN",
+                (false, "02") => "CONNECTING",
+                (false, "03") => "CONNECTED",
+                (false, "04") => "DISCONNECTING",
+                _ => "UNKNOWN",
+            };
+            let protocol = match *fields.get(4)? {
+                "0002" => "unix-dgram",
+                "0005" => "unix-seqpacket",
+                _ => "unix",
+            };
+            Some(Socket {
+                protocol,
+                local: fields.get(7).unwrap_or(&"").to_string(),
+                remote: String::new(),
+                state,
+                inode: fields.get(6)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+// Every TCP and UDP socket in the network namespace of `net_dir`, which is
+// /proc/net or a process's /proc/<pid>/net
+pub fn read_inet_sockets(net_dir: &Path) -> Vec<Socket> {
+    let mut sockets = Vec::new();
+    sockets.extend(read_inet(&net_dir.join("tcp"), "tcp", false));
+    sockets.extend(read_inet(&net_dir.join("tcp6"), "tcp6", false));
+    sockets.extend(read_inet(&net_dir.join("udp"), "udp", true));
+    sockets.extend(read_inet(&net_dir.join("udp6"), "udp6", true));
+    sockets
+}
+
+// The TCP and UDP sockets plus Unix sockets
+pub fn read_sockets(net_dir: &Path) -> Vec<Socket> {
+    let mut sockets = read_inet_sockets(net_dir);
+    sockets.extend(read_unix(&net_dir.join("unix")));
+    sockets
+}
+
+// The inode in an fd link such as "socket:[31306]"
+pub fn socket_inode(target: &str) -> Option<u64> {
+    target
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
@@ -1,5 +1,15 @@
-use sysinfo::{Disks, System};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use sysinfo::{Components, Networks, System};
 
+pub const PRESSURE_ROOT: &str = "/proc/pressure";
+pub const MEMINFO_PATH: &str = "/proc/meminfo";
+pub const STAT_PATH: &str = "/proc/stat";
+
+#[derive(Default, Serialize, Deserialize)]
 pub struct SystemStats {
     pub host_name: Option<String>,
     pub os_version: Option<String>,
@@ -12,55 +22,216 @@ pub struct SystemStats {
     pub total_memory: u64,
     pub used_memory: u64,
     pub free_memory: u64,
+    // The kernel's MemAvailable estimate, which counts reclaimable cache
+    pub available_memory: u64,
+    pub total_swap: u64,
+    pub used_swap: u64,
+    // 1, 5 and 15 minute averages. Defaulted so agents that predate it
+    // still parse.
+    #[serde(default)]
+    pub load_average: [f64; 3],
 }
 
-pub struct DisksStats {
-    pub disk_names: Vec<String>,
-    pub disk_mnt_pts: Vec<String>,
-    pub disk_usages: Vec<String>,
-    pub disk_filesystems: Vec<String>,
-    pub disk_kinds: Vec<String>,
+// The parts of /proc/meminfo behind the Memory tab's breakdown, in bytes
+#[derive(Serialize, Deserialize)]
+pub struct MemInfo {
+    pub total: u64,
+    pub free: u64,
+    pub available: u64,
+    pub buffers: u64,
+    pub cached: u64,
+    pub swap_cached: u64,
+    pub shared: u64,
+    pub slab_reclaimable: u64,
+    pub slab_unreclaimable: u64,
+    pub dirty: u64,
+    pub writeback: u64,
+    pub committed: u64,
+    pub commit_limit: u64,
+    pub huge_pages_total: u64,
+    pub huge_pages_free: u64,
+    pub huge_page_size: u64,
 }
 
-pub fn collect_disks_stats() -> DisksStats {
-    let disks = Disks::new_with_refreshed_list();
-    let disk_list = disks.list();
-    let disk_names = disk_list
-        .iter()
-        .map(|disk| disk.name().to_string_lossy().to_string())
-        .collect::<Vec<String>>();
-    let disk_mnts = disk_list
-        .iter()
-        .map(|disk| disk.mount_point().to_string_lossy().to_string())
-        .collect::<Vec<String>>();
-    let disk_systems = disk_list
-        .iter()
-        .map(|disk| disk.file_system().to_string_lossy().to_string())
-        .collect::<Vec<String>>();
-    let disk_kinds = disk_list
-        .iter()
-        .map(|disk| disk.kind().to_string())
-        .collect::<Vec<String>>();
-    let mut disk_usgs: Vec<String> = Vec::new();
-    for disk in disks.list() {
-        let total_space = disk.total_space();
-        let available_space = disk.available_space();
-        let used_space = total_space - available_space;
-        let percentage_used = ((used_space as f64 / total_space as f64) * 100.0) as f32;
-        disk_usgs.push(percentage_used.to_string());
+impl MemInfo {
+    // Page cache the kernel can drop, counted like free(1)'s buff/cache
+    pub fn cache(&self) -> u64 {
+        self.cached + self.slab_reclaimable
     }
 
-    DisksStats {
-        disk_names: disk_names,
-        disk_mnt_pts: disk_mnts,
-        disk_usages: disk_usgs,
-        disk_filesystems: disk_systems,
-        disk_kinds: disk_kinds,
+    // What is left once free memory, buffers and cache are taken out
+    pub fn used(&self) -> u64 {
+        self.total
+            .saturating_sub(self.free)
+            .saturating_sub(self.buffers)
+            .saturating_sub(self.cache())
+    }
+}
+
+// Cumulative jiffies from one "cpu" line of /proc/stat
+#[derive(Clone, Copy, Default)]
+pub struct CpuTimes {
+    user: u64,
+    nice: u64,
+    system: u64,
+    idle: u64,
+    iowait: u64,
+    irq: u64,
+    softirq: u64,
+    steal: u64,
+}
+
+impl CpuTimes {
+    // Guest time is already included in user and nice, so it is not added
+    fn total(&self) -> u64 {
+        self.user
+            + self.nice
+            + self.system
+            + self.idle
+            + self.iowait
+            + self.irq
+            + self.softirq
+            + self.steal
+    }
+}
+
+// Percentage of an interval spent in each state; the fields add up to 100
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct CpuBreakdown {
+    pub user: f32,
+    pub nice: f32,
+    pub system: f32,
+    pub iowait: f32,
+    pub irq: f32,
+    pub softirq: f32,
+    // Time a hypervisor ran something else while this vCPU was runnable
+    pub steal: f32,
+    pub idle: f32,
+}
+
+impl CpuBreakdown {
+    // Counters can step backwards when a core goes offline and comes back,
+    // so each delta saturates at zero
+    pub fn between(previous: &CpuTimes, current: &CpuTimes) -> CpuBreakdown {
+        let total = current.total().saturating_sub(previous.total());
+        if total == 0 {
+            return CpuBreakdown {
+                idle: 100.0,
+                ..CpuBreakdown::default()
+            };
+        }
+        let share =
+            |now: u64, before: u64| now.saturating_sub(before) as f32 / total as f32 * 100.0;
+        CpuBreakdown {
+            user: share(current.user, previous.user),
+            nice: share(current.nice, previous.nice),
+            system: share(current.system, previous.system),
+            iowait: share(current.iowait, previous.iowait),
+            irq: share(current.irq, previous.irq),
+            softirq: share(current.softirq, previous.softirq),
+            steal: share(current.steal, previous.steal),
+            idle: share(current.idle, previous.idle),
+        }
     }
 }
 
-pub fn collect_system_stats(sys: &mut System) -> SystemStats {
-    sys.refresh_all();
+// The aggregate "cpu" line and one entry per online core, in file order
+pub struct CpuStat {
+    pub total: CpuTimes,
+    pub cores: Vec<CpuTimes>,
+}
+
+fn parse_cpu_times(fields: &[&str]) -> CpuTimes {
+    let field = |i: usize| fields.get(i).and_then(|v| v.parse().ok()).unwrap_or(0);
+    CpuTimes {
+        user: field(0),
+        nice: field(1),
+        system: field(2),
+        idle: field(3),
+        iowait: field(4),
+        irq: field(5),
+        softirq: field(6),
+        steal: field(7),
+    }
+}
+
+// None when the file cannot be read, e.g. outside Linux
+pub fn read_cpu_stat(path: &Path) -> Option<CpuStat> {
+    let contents = fs::read_to_string(path).ok()?;
+    let mut total = None;
+    let mut cores = Vec::new();
+    for line in contents.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(name) = fields.next() else {
+            continue;
+        };
+        let fields: Vec<&str> = fields.collect();
+        if name == "cpu" {
+            total = Some(parse_cpu_times(&fields));
+        } else if name.starts_with("cpu") {
+            cores.push(parse_cpu_times(&fields));
+        }
+    }
+    Some(CpuStat {
+        total: total?,
+        cores,
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PressureValues {
+    pub avg10: f32,
+    pub avg60: f32,
+    pub avg300: f32,
+    pub total: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Pressure {
+    pub some: PressureValues,
+    // The kernel omits "full" for cpu before 5.13
+    pub full: Option<PressureValues>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct PressureStats {
+    pub cpu: Option<Pressure>,
+    pub memory: Option<Pressure>,
+    pub io: Option<Pressure>,
+}
+
+impl PressureStats {
+    pub fn available(&self) -> bool {
+        self.cpu.is_some() || self.memory.is_some() || self.io.is_some()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NetworkInterface {
+    pub name: String,
+    // Bytes per second since the previous refresh
+    pub rx_rate: u64,
+    pub tx_rate: u64,
+    pub total_rx: u64,
+    pub total_tx: u64,
+    pub total_rx_packets: u64,
+    pub total_tx_packets: u64,
+    pub total_errors: u64,
+    pub mac: String,
+    pub addresses: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SensorReading {
+    pub label: String,
+    pub temperature: Option<f32>,
+    pub max: Option<f32>,
+    pub critical: Option<f32>,
+}
+
+// Reads the figures of the caller's latest refresh. Refreshing again here
+// would measure CPU usage over the few microseconds since that refresh.
+pub fn collect_system_stats(sys: &System) -> SystemStats {
     let cpu_names = sys
         .cpus()
         .iter()
@@ -78,11 +249,210 @@ pub fn collect_system_stats(sys: &mut System) -> SystemStats {
         uptime: System::uptime(),
         arch: System::cpu_arch(),
         os_name: System::name(),
-        cpu_names: cpu_names,
+        cpu_names,
         cpu_global_usage: sys.global_cpu_usage(),
         cpu_cores: cpu_cores_usage,
         total_memory: sys.total_memory(),
         used_memory: sys.used_memory(),
         free_memory: sys.free_memory(),
+        available_memory: sys.available_memory(),
+        total_swap: sys.total_swap(),
+        used_swap: sys.used_swap(),
+        load_average: {
+            let load = System::load_average();
+            [load.one, load.five, load.fifteen]
+        },
+    }
+}
+
+pub fn collect_network_stats(networks: &Networks, interval: Duration) -> Vec<NetworkInterface> {
+    let secs = interval.as_secs_f64().max(0.001);
+    let mut interfaces = networks
+        .list()
+        .iter()
+        .map(|(name, data)| NetworkInterface {
+            name: name.clone(),
+            rx_rate: (data.received() as f64 / secs) as u64,
+            tx_rate: (data.transmitted() as f64 / secs) as u64,
+            total_rx: data.total_received(),
+            total_tx: data.total_transmitted(),
+            total_rx_packets: data.total_packets_received(),
+            total_tx_packets: data.total_packets_transmitted(),
+            total_errors: data.total_errors_on_received() + data.total_errors_on_transmitted(),
+            mac: data.mac_address().to_string(),
+            addresses: data
+                .ip_networks()
+                .iter()
+                .map(|ip| format!("{}/{}", ip.addr, ip.prefix))
+                .collect(),
+        })
+        .collect::<Vec<NetworkInterface>>();
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    interfaces
+}
+
+pub fn collect_sensors(components: &Components) -> Vec<SensorReading> {
+    components
+        .list()
+        .iter()
+        .map(|component| SensorReading {
+            label: component.label().to_string(),
+            temperature: component.temperature(),
+            max: component.max(),
+            critical: component.critical(),
+        })
+        .collect()
+}
+
+fn parse_pressure_values(fields: &str) -> Option<PressureValues> {
+    let mut values = PressureValues {
+        avg10: 0.0,
+        avg60: 0.0,
+        avg300: 0.0,
+        total: 0,
+    };
+    for field in fields.split_whitespace() {
+        let (key, value) = field.split_once('=')?;
+        match key {
+            "avg10" => values.avg10 = value.parse().ok()?,
+            "avg60" => values.avg60 = value.parse().ok()?,
+            "avg300" => values.avg300 = value.parse().ok()?,
+            "total" => values.total = value.parse().ok()?,
+            _ => {}
+        }
+    }
+    Some(values)
+}
+
+pub fn parse_pressure(contents: &str) -> Option<Pressure> {
+    let mut some = None;
+    let mut full = None;
+    for line in contents.lines() {
+        if let Some(fields) = line.strip_prefix("some ") {
+            some = parse_pressure_values(fields);
+        } else if let Some(fields) = line.strip_prefix("full ") {
+            full = parse_pressure_values(fields);
+        }
+    }
+    Some(Pressure { some: some?, full })
+}
+
+// Most lines are "Name:   1234 kB"; the HugePages_ counts have no unit.
+// None when the file cannot be read, e.g. outside Linux.
+pub fn read_meminfo(path: &Path) -> Option<MemInfo> {
+    let contents = fs::read_to_string(path).ok()?;
+    let mut values = HashMap::new();
+    for line in contents.lines() {
+        let Some((name, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let mut parts = rest.split_whitespace();
+        let Some(Ok(value)) = parts.next().map(|v| v.parse::<u64>()) else {
+            continue;
+        };
+        let value = match parts.next() {
+            Some("kB") => value * 1024,
+            _ => value,
+        };
+        values.insert(name, value);
+    }
+    let get = |name: &str| values.get(name).copied().unwrap_or(0);
+    Some(MemInfo {
+        total: get("MemTotal"),
+        free: get("MemFree"),
+        available: get("MemAvailable"),
+        buffers: get("Buffers"),
+        cached: get("Cached"),
+        swap_cached: get("SwapCached"),
+        shared: get("Shmem"),
+        slab_reclaimable: get("SReclaimable"),
+        slab_unreclaimable: get("SUnreclaim"),
+        dirty: get("Dirty"),
+        writeback: get("Writeback"),
+        committed: get("Committed_AS"),
+        commit_limit: get("CommitLimit"),
+        huge_pages_total: get("HugePages_Total"),
+        huge_pages_free: get("HugePages_Free"),
+        huge_page_size: get("Hugepagesize"),
+    })
+}
+
+// `root` is normally PRESSURE_ROOT; kernels without CONFIG_PSI (or with
+// psi=0) have no such directory and every resource comes back as `None`.
+pub fn collect_pressure_stats(root: &Path) -> PressureStats {
+    let read = |resource: &str| {
+        fs::read_to_string(root.join(resource))
+            .ok()
+            .and_then(|contents| parse_pressure(&contents))
+    };
+
+    PressureStats {
+        cpu: read("cpu"),
+        memory: read("memory"),
+        io: read("io"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::path::PathBuf;
+    use std::process;
+
+    const MEMORY: &str = "some avg10=1.50 avg60=0.75 avg300=0.25 total=123456\n\
+                          full avg10=0.50 avg60=0.10 avg300=0.00 total=4567\n";
+    // Kernels before 5.13 have no "full" line for CPU
+    const CPU: &str = "some avg10=12.00 avg60=8.00 avg300=4.00 total=987654\n";
+
+    // A scratch directory per test so they can run in parallel
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("resource-manager-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn parses_some_and_full() {
+        let pressure = parse_pressure(MEMORY).unwrap();
+        assert_eq!(pressure.some.avg10, 1.5);
+        assert_eq!(pressure.some.avg60, 0.75);
+        assert_eq!(pressure.some.avg300, 0.25);
+        assert_eq!(pressure.some.total, 123456);
+        let full = pressure.full.unwrap();
+        assert_eq!(full.avg10, 0.5);
+        assert_eq!(full.total, 4567);
+    }
+
+    #[test]
+    fn parses_cpu_without_full() {
+        let pressure = parse_pressure(CPU).unwrap();
+        assert_eq!(pressure.some.avg10, 12.0);
+        assert!(pressure.full.is_none());
+    }
+
+    #[test]
+    fn collects_from_directory() {
+        let dir = scratch_dir("psi");
+        fs::write(dir.join("cpu"), CPU).unwrap();
+        fs::write(dir.join("memory"), MEMORY).unwrap();
+        let stats = collect_pressure_stats(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(stats.cpu.unwrap().full.is_none());
+        assert_eq!(stats.memory.unwrap().full.unwrap().avg10, 0.5);
+        assert!(stats.io.is_none());
+    }
+
+    #[test]
+    fn missing_directory_is_unavailable() {
+        let dir = scratch_dir("no-psi");
+        let stats = collect_pressure_stats(&dir.join("pressure"));
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(stats.cpu.is_none());
+        assert!(stats.memory.is_none());
+        assert!(stats.io.is_none());
+        assert!(!stats.available());
     }
 }
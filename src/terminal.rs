@@ -0,0 +1,72 @@
+use crossterm::{
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use signal_hook::consts::SIGTSTP;
+use signal_hook::low_level::emulate_default_handler;
+use std::io::{self, Result};
+use std::panic;
+
+// Puts the terminal into raw mode on the alternate screen and restores it when
+// dropped, so every way out of the main loop (return, `?`, panic unwinding)
+// leaves the shell usable.
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn enter() -> Result<TerminalGuard> {
+        enable_raw_mode()?;
+        // From here on the guard exists, so a failure below still restores
+        let guard = TerminalGuard;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+// Safe to call more than once and when the terminal was never set up
+pub fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        io::stdout(),
+        DisableMouseCapture,
+        LeaveAlternateScreen,
+        Show
+    );
+}
+
+// Hands the terminal back to the shell and stops the process the way Ctrl-Z
+// normally would; returns once the shell continues us with SIGCONT
+pub fn suspend() -> Result<()> {
+    restore();
+    emulate_default_handler(SIGTSTP)
+}
+
+// Re-enters raw mode and the alternate screen after a suspend. The caller
+// has to clear the tui buffers so the next draw repaints everything.
+pub fn resume(mouse_capture: bool) -> Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    if mouse_capture {
+        execute!(io::stdout(), EnableMouseCapture)?;
+    }
+    Ok(())
+}
+
+// Restores the terminal before the default hook prints the panic message, so
+// the report lands on the normal screen instead of vanishing with the
+// alternate one.
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore();
+        eprintln!("resource-manager crashed; the terminal has been restored.");
+        default_hook(info);
+    }));
+}
@@ -0,0 +1,248 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use tui::style::{Color, Modifier, Style};
+use tui::text::Span;
+
+pub const BUILTIN_THEMES: [&str; 5] =
+    ["dark", "light", "high-contrast", "colorblind", "monochrome"];
+
+#[derive(Clone)]
+pub struct Theme {
+    pub ok: Style,
+    pub warning: Style,
+    pub critical: Style,
+    pub header: Style,
+    pub border: Style,
+    pub title: Style,
+    pub selection: Style,
+    pub chart: Style,
+    pub text: Style,
+}
+
+// A custom theme starts from `base` and overrides individual entries with
+// colour names ("light-red"), hex ("#d55e00") or 256-colour indices ("208").
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub base: Option<String>,
+    pub ok: Option<String>,
+    pub warning: Option<String>,
+    pub critical: Option<String>,
+    pub header: Option<String>,
+    pub border: Option<String>,
+    pub title: Option<String>,
+    // Background colour of the selected row
+    pub selection: Option<String>,
+    pub chart: Option<String>,
+    pub text: Option<String>,
+}
+
+fn fg(color: Color) -> Style {
+    Style::default().fg(color)
+}
+
+impl Theme {
+    pub fn dark() -> Theme {
+        Theme {
+            ok: fg(Color::LightGreen),
+            warning: fg(Color::LightYellow),
+            critical: fg(Color::LightRed),
+            header: fg(Color::Yellow),
+            border: Style::default(),
+            title: Style::default(),
+            selection: Style::default().add_modifier(Modifier::BOLD),
+            chart: fg(Color::LightYellow),
+            text: Style::default(),
+        }
+    }
+
+    pub fn light() -> Theme {
+        Theme {
+            ok: fg(Color::Indexed(28)),
+            warning: fg(Color::Indexed(130)),
+            critical: fg(Color::Indexed(160)).add_modifier(Modifier::BOLD),
+            header: fg(Color::Indexed(19)).add_modifier(Modifier::BOLD),
+            border: fg(Color::Indexed(244)),
+            title: fg(Color::Indexed(19)),
+            selection: Style::default()
+                .bg(Color::Indexed(254))
+                .add_modifier(Modifier::BOLD),
+            chart: fg(Color::Indexed(25)),
+            text: fg(Color::Black),
+        }
+    }
+
+    pub fn high_contrast() -> Theme {
+        let bold = Modifier::BOLD;
+        Theme {
+            ok: fg(Color::White),
+            warning: fg(Color::Yellow).add_modifier(bold),
+            critical: Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightRed)
+                .add_modifier(bold),
+            header: fg(Color::White).add_modifier(bold | Modifier::UNDERLINED),
+            border: fg(Color::White),
+            title: fg(Color::White).add_modifier(bold),
+            selection: Style::default()
+                .fg(Color::Black)
+                .bg(Color::White)
+                .add_modifier(bold),
+            chart: fg(Color::White),
+            text: fg(Color::White),
+        }
+    }
+
+    // Okabe-Ito palette: blue/orange/vermillion stay distinct for the common
+    // forms of colour blindness, and critical is bold as a second cue.
+    pub fn colorblind() -> Theme {
+        Theme {
+            ok: fg(Color::Rgb(0x56, 0xb4, 0xe9)),
+            warning: fg(Color::Rgb(0xe6, 0x9f, 0x00)),
+            critical: fg(Color::Rgb(0xd5, 0x5e, 0x00)).add_modifier(Modifier::BOLD),
+            header: fg(Color::Rgb(0xf0, 0xe4, 0x42)),
+            border: Style::default(),
+            title: Style::default(),
+            selection: Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            chart: fg(Color::Rgb(0x00, 0x9e, 0x73)),
+            text: Style::default(),
+        }
+    }
+
+    // No colours at all; severity is carried by text attributes only
+    pub fn monochrome() -> Theme {
+        Theme {
+            ok: Style::default(),
+            warning: Style::default().add_modifier(Modifier::BOLD),
+            critical: Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            header: Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            border: Style::default(),
+            title: Style::default().add_modifier(Modifier::BOLD),
+            selection: Style::default().add_modifier(Modifier::REVERSED),
+            chart: Style::default(),
+            text: Style::default(),
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "colorblind" => Some(Theme::colorblind()),
+            "monochrome" => Some(Theme::monochrome()),
+            _ => None,
+        }
+    }
+
+    // Percent-style usage values: >75.5% critical, >50% warning
+    pub fn severity(&self, s: String, num: f32) -> Span<'static> {
+        if num > 75.5 {
+            return Span::styled(s, self.critical);
+        } else if num > 50.0 {
+            return Span::styled(s, self.warning);
+        }
+        Span::styled(s, self.ok)
+    }
+
+    // PSI averages are the share of time stalled, so even 10% is worth noticing
+    pub fn pressure(&self, s: String, num: f32) -> Span<'static> {
+        if num > 25.0 {
+            return Span::styled(s, self.critical);
+        } else if num > 10.0 {
+            return Span::styled(s, self.warning);
+        }
+        Span::styled(s, self.ok)
+    }
+}
+
+pub fn parse_color(s: &str) -> Option<Color> {
+    let s = s.trim().to_ascii_lowercase();
+    if let Some(hex) = s.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+    if let Ok(index) = s.parse::<u8>() {
+        return Some(Color::Indexed(index));
+    }
+    let color = match s.replace(['_', ' '], "-").as_str() {
+        "reset" | "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "dark-gray" | "dark-grey" => Color::DarkGray,
+        "light-red" => Color::LightRed,
+        "light-green" => Color::LightGreen,
+        "light-yellow" => Color::LightYellow,
+        "light-blue" => Color::LightBlue,
+        "light-magenta" => Color::LightMagenta,
+        "light-cyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+    Some(color)
+}
+
+fn build_custom(name: &str, custom: &ThemeConfig) -> Result<Theme, String> {
+    let base = custom.base.as_deref().unwrap_or("dark");
+    let mut theme = Theme::builtin(base)
+        .ok_or_else(|| format!("theme {}: unknown base theme \"{}\"", name, base))?;
+    let color = |field: &str, value: &Option<String>| -> Result<Option<Color>, String> {
+        match value {
+            Some(value) => parse_color(value)
+                .map(Some)
+                .ok_or_else(|| format!("theme {}: invalid {} colour \"{}\"", name, field, value)),
+            None => Ok(None),
+        }
+    };
+
+    let entries = [
+        ("ok", &custom.ok, &mut theme.ok),
+        ("warning", &custom.warning, &mut theme.warning),
+        ("critical", &custom.critical, &mut theme.critical),
+        ("header", &custom.header, &mut theme.header),
+        ("border", &custom.border, &mut theme.border),
+        ("title", &custom.title, &mut theme.title),
+        ("chart", &custom.chart, &mut theme.chart),
+        ("text", &custom.text, &mut theme.text),
+    ];
+    for (field, value, style) in entries {
+        if let Some(c) = color(field, value)? {
+            *style = style.fg(c);
+        }
+    }
+    if let Some(c) = color("selection", &custom.selection)? {
+        theme.selection = theme.selection.bg(c);
+    }
+    Ok(theme)
+}
+
+// NO_COLOR (https://no-color.org) wins over any configured theme
+pub fn resolve_theme(
+    name: Option<&str>,
+    custom: &HashMap<String, ThemeConfig>,
+) -> Result<Theme, String> {
+    if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return Ok(Theme::monochrome());
+    }
+    let name = name.unwrap_or("dark");
+    if let Some(theme) = custom.get(name) {
+        return build_custom(name, theme);
+    }
+    Theme::builtin(name).ok_or_else(|| {
+        format!(
+            "unknown theme \"{}\" (built-in themes: {})",
+            name,
+            BUILTIN_THEMES.join(", ")
+        )
+    })
+}
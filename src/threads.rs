@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ThreadSort {
+    Tid,
+    Name,
+    Cpu,
+}
+
+impl ThreadSort {
+    pub fn next(&self) -> ThreadSort {
+        match self {
+            ThreadSort::Tid => ThreadSort::Name,
+            ThreadSort::Name => ThreadSort::Cpu,
+            ThreadSort::Cpu => ThreadSort::Tid,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ThreadSort::Tid => "TID",
+            ThreadSort::Name => "Name",
+            ThreadSort::Cpu => "CPU",
+        }
+    }
+}
+
+pub struct ThreadInfo {
+    pub tid: u32,
+    pub name: String,
+    // Single-letter state from /proc/<pid>/task/<tid>/stat (R, S, D, Z, ...)
+    pub state: char,
+    // Percentage of one core since the previous sample
+    pub cpu: f32,
+    // Cpus_allowed_list, e.g. "0-3,8"
+    pub affinity: String,
+}
+
+// Per-thread CPU needs two readings of utime + stime, so the sampler keeps
+// the previous one for the process it is following.
+pub struct ThreadSampler {
+    pid: u32,
+    ticks: HashMap<u32, u64>,
+    last: Option<Instant>,
+}
+
+fn clock_ticks() -> f32 {
+    // SAFETY: sysconf has no preconditions and only reads a constant
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 {
+        ticks as f32
+    } else {
+        100.0
+    }
+}
+
+// Returns (name, state, utime + stime) from a stat line. The name sits in
+// parentheses and may itself contain spaces or parentheses.
+fn parse_stat(stat: &str) -> Option<(String, char, u64)> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?.to_string();
+    let fields: Vec<&str> = stat.get(close + 1..)?.split_whitespace().collect();
+    let state = fields.first()?.chars().next()?;
+    // utime and stime are fields 14 and 15 of the whole line
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some((name, state, utime + stime))
+}
+
+fn read_affinity(task: &Path) -> String {
+    fs::read_to_string(task.join("status"))
+        .ok()
+        .and_then(|status| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix("Cpus_allowed_list:"))
+                .map(|list| list.trim().to_string())
+        })
+        .unwrap_or_default()
+}
+
+impl ThreadSampler {
+    pub fn new(pid: u32) -> ThreadSampler {
+        ThreadSampler {
+            pid,
+            ticks: HashMap::new(),
+            last: None,
+        }
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    // Reads every thread of the process; an exited process yields no rows.
+    // The first sample has no baseline, so its CPU figures are zero.
+    pub fn sample(&mut self, root: &Path, now: Instant) -> Vec<ThreadInfo> {
+        let elapsed = self.last.map(|t| now.duration_since(t).as_secs_f32());
+        let per_tick = match elapsed {
+            Some(secs) if secs > 0.0 => 100.0 / (secs * clock_ticks()),
+            _ => 0.0,
+        };
+        let mut ticks = HashMap::new();
+        let mut threads = Vec::new();
+
+        let task_dir = root.join(self.pid.to_string()).join("task");
+        let entries = match fs::read_dir(&task_dir) {
+            Ok(entries) => entries,
+            Err(_) => return threads,
+        };
+        for entry in entries.flatten() {
+            let Some(tid) = entry.file_name().to_str().and_then(|s| s.parse().ok()) else {
+                continue;
+            };
+            let task = entry.path();
+            // The thread may exit between listing and reading
+            let Some((name, state, total)) = fs::read_to_string(task.join("stat"))
+                .ok()
+                .and_then(|stat| parse_stat(&stat))
+            else {
+                continue;
+            };
+            let cpu = match self.ticks.get(&tid) {
+                Some(previous) => total.saturating_sub(*previous) as f32 * per_tick,
+                None => 0.0,
+            };
+            ticks.insert(tid, total);
+            threads.push(ThreadInfo {
+                tid,
+                name,
+                state,
+                cpu,
+                affinity: read_affinity(&task),
+            });
+        }
+
+        self.ticks = ticks;
+        self.last = Some(now);
+        threads
+    }
+}
+
+// Ties fall back to TID so rows keep a stable order between refreshes
+pub fn sort_threads(threads: &mut [ThreadInfo], column: ThreadSort, descending: bool) {
+    threads.sort_by(|a, b| {
+        let ordering = match column {
+            ThreadSort::Tid => a.tid.cmp(&b.tid),
+            ThreadSort::Name => a.name.cmp(&b.name),
+            ThreadSort::Cpu => a.cpu.total_cmp(&b.cpu),
+        };
+        let ordering = if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        ordering.then(a.tid.cmp(&b.tid))
+    });
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use sysinfo::System;
+
+pub const CPU_SYS_ROOT: &str = "/sys/devices/system/cpu";
+
+#[derive(Serialize, Deserialize)]
+pub struct LogicalCpu {
+    // "cpu3", as sysinfo and /proc/stat name it
+    pub name: String,
+    pub usage: f32,
+    // Socket and physical core from topology/; None where sysfs lacks them
+    pub package: Option<u32>,
+    pub core: Option<u32>,
+    // Current frequency in MHz
+    pub frequency: u64,
+    // cpufreq scaling limits in MHz and governor; None without cpufreq,
+    // which is common in VMs
+    pub min_frequency: Option<u64>,
+    pub max_frequency: Option<u64>,
+    pub governor: Option<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct CpuTopology {
+    pub brand: String,
+    pub vendor: String,
+    pub cpus: Vec<LogicalCpu>,
+}
+
+// Logical CPUs of one physical core, keyed by core id
+pub type CoreGroup<'a> = BTreeMap<Option<u32>, Vec<&'a LogicalCpu>>;
+
+impl CpuTopology {
+    // Sockets, then physical cores, each in id order
+    pub fn grouped(&self) -> BTreeMap<Option<u32>, CoreGroup<'_>> {
+        let mut sockets: BTreeMap<Option<u32>, CoreGroup> = BTreeMap::new();
+        for cpu in &self.cpus {
+            sockets
+                .entry(cpu.package)
+                .or_default()
+                .entry(cpu.core)
+                .or_default()
+                .push(cpu);
+        }
+        sockets
+    }
+
+    pub fn sockets(&self) -> usize {
+        self.cpus
+            .iter()
+            .map(|cpu| cpu.package)
+            .collect::<BTreeSet<_>>()
+            .len()
+    }
+
+    // Core ids are only unique within a socket
+    pub fn physical_cores(&self) -> usize {
+        self.cpus
+            .iter()
+            .map(|cpu| (cpu.package, cpu.core))
+            .collect::<BTreeSet<_>>()
+            .len()
+    }
+}
+
+fn read_value<T: std::str::FromStr>(path: &Path) -> Option<T> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+// The ids and scaling limits of one logical CPU, which sysfs only changes on
+// hotplug or when an administrator retunes cpufreq
+struct FixedCpu {
+    package: Option<u32>,
+    core: Option<u32>,
+    min_frequency: Option<u64>,
+    max_frequency: Option<u64>,
+}
+
+// Reads each CPU's fixed values from sysfs the first time it is seen, so a
+// refresh only reads the governor
+pub struct TopologyReader {
+    // Normally CPU_SYS_ROOT
+    root: PathBuf,
+    fixed: HashMap<String, FixedCpu>,
+}
+
+impl TopologyReader {
+    pub fn new(root: &Path) -> TopologyReader {
+        TopologyReader {
+            root: root.to_path_buf(),
+            fixed: HashMap::new(),
+        }
+    }
+
+    pub fn collect(&mut self, sys: &System) -> CpuTopology {
+        let cpus = sys
+            .cpus()
+            .iter()
+            .map(|cpu| {
+                let dir = self.root.join(cpu.name());
+                let cpufreq = dir.join("cpufreq");
+                // scaling_*_freq are in kHz
+                let khz = |file: &str| read_value::<u64>(&cpufreq.join(file)).map(|f| f / 1000);
+                let fixed = self.fixed.entry(cpu.name().to_string()).or_insert_with(|| {
+                    let topology = dir.join("topology");
+                    FixedCpu {
+                        package: read_value(&topology.join("physical_package_id")),
+                        core: read_value(&topology.join("core_id")),
+                        min_frequency: khz("scaling_min_freq"),
+                        max_frequency: khz("scaling_max_freq"),
+                    }
+                });
+                LogicalCpu {
+                    name: cpu.name().to_string(),
+                    usage: cpu.cpu_usage(),
+                    package: fixed.package,
+                    core: fixed.core,
+                    frequency: cpu.frequency(),
+                    min_frequency: fixed.min_frequency,
+                    max_frequency: fixed.max_frequency,
+                    governor: read_value(&cpufreq.join("scaling_governor")),
+                }
+            })
+            .collect();
+        let first = sys.cpus().first();
+        CpuTopology {
+            brand: first
+                .map(|cpu| cpu.brand().trim().to_string())
+                .unwrap_or_default(),
+            vendor: first
+                .map(|cpu| cpu.vendor_id().to_string())
+                .unwrap_or_default(),
+            cpus,
+        }
+    }
+}
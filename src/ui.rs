@@ -1,34 +1,254 @@
-use crate::processes::ProcessInfo;
-use crate::system::{DisksStats, SystemStats};
+mod connections;
+mod cpu;
+mod detail;
+mod disks;
+mod events;
+mod fleet;
+mod help;
+mod memory;
+mod network;
+mod sensors;
+mod threads;
+
+use crate::alerts::{AlertEngine, AlertState};
+use crate::app::{App, ProcessView, Regions, Tab, HISTORY_LEN, TABS};
+use crate::disks::DiskInfo;
+use crate::processes::{ProcessInfo, SortColumn, StateCounts};
+use crate::remote::RemoteStatus;
+use crate::snapshot::Snapshot;
+use crate::system::{CpuBreakdown, Pressure, PressureStats, SystemStats};
+use crate::theme::Theme;
+pub use fleet::draw_fleet;
+use std::collections::VecDeque;
+use std::time::Instant;
 use tui::{
     backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
-    text::Span,
-    widgets::{Block, Borders, Paragraph, Row, Table},
+    style::{Modifier, Style},
+    symbols,
+    text::{Span, Spans},
+    widgets::{
+        Axis, Block, Borders, Cell, Chart, Dataset, GraphType, Paragraph, Row, Sparkline, Table,
+        TableState, Tabs,
+    },
     Frame,
 };
 
-fn color_severity(s: String, num: f32) -> Span<'static> {
-    if num > 75.5 {
-        return Span::styled(s, Style::default().fg(Color::LightRed));
-    } else if num > 50.0 {
-        return Span::styled(s, Style::default().fg(Color::LightYellow));
+fn format_mb(bytes: u64) -> String {
+    format!("{:.2}", bytes as f64 / 1000000.0)
+}
+
+// Decimal units, matching the MB/GB figures elsewhere in the UI
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.2} {}", value, UNITS[unit])
+    }
+}
+
+// Process CPU histories are kept per core; this converts one into the mode
+// on screen.
+fn scaled_cpu(app: &App, samples: &VecDeque<u64>) -> VecDeque<u64> {
+    samples
+        .iter()
+        .map(|v| app.cpu_mode.scale(*v as f32, app.cpu_count) as u64)
+        .collect()
+}
+
+// Renders the newest `width` samples as block characters scaled to `floor`
+// or the window maximum, whichever is larger.
+fn text_sparkline(samples: &VecDeque<u64>, width: usize, floor: u64) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let skip = samples.len().saturating_sub(width);
+    let max = samples
+        .iter()
+        .skip(skip)
+        .cloned()
+        .max()
+        .unwrap_or(0)
+        .max(floor)
+        .max(1);
+    samples
+        .iter()
+        .skip(skip)
+        .map(|v| BARS[((v * 7) / max).min(7) as usize])
+        .collect()
+}
+
+// One row split into coloured runs proportional to each (amount, style,
+// fill) segment. The last segment takes whatever rounding left over.
+fn stacked_bar(segments: &[(f64, Style, char)], total: f64, width: usize) -> Vec<Span<'static>> {
+    let total = total.max(f64::MIN_POSITIVE);
+    let mut spans = Vec::new();
+    let mut drawn = 0;
+    for (i, (amount, style, fill)) in segments.iter().enumerate() {
+        let cells = if i == segments.len() - 1 {
+            width.saturating_sub(drawn)
+        } else {
+            ((amount / total * width as f64).round() as usize).min(width.saturating_sub(drawn))
+        };
+        drawn += cells;
+        spans.push(Span::styled(fill.to_string().repeat(cells), *style));
+    }
+    spans
+}
+
+fn titled_block(title: String, theme: &Theme) -> Block<'static> {
+    Block::default()
+        .title(Span::styled(title, theme.title))
+        .borders(Borders::ALL)
+        .border_style(theme.border)
+}
+
+// Line chart of histories sharing one y axis. Samples are divided by `scale`
+// and the axis runs from 0 to `max` or the largest sample, whichever is larger;
+// the unit belongs in the title. The x axis spans HISTORY_LEN samples or the
+// longest series.
+fn draw_history_chart<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    title: String,
+    series: &[(&str, &VecDeque<u64>, Style)],
+    scale: f64,
+    max: f64,
+    area: Rect,
+) {
+    let window = series
+        .iter()
+        .map(|(_, samples, _)| samples.len())
+        .fold(HISTORY_LEN, usize::max);
+    let points: Vec<Vec<(f64, f64)>> = series
+        .iter()
+        .map(|(_, samples, _)| {
+            // Right-align so the newest sample is always at the right edge
+            let offset = window - samples.len();
+            samples
+                .iter()
+                .enumerate()
+                .map(|(i, v)| ((offset + i) as f64, *v as f64 / scale))
+                .collect()
+        })
+        .collect();
+    let max = points
+        .iter()
+        .flatten()
+        .map(|(_, y)| *y)
+        .fold(max, f64::max)
+        .max(f64::EPSILON);
+    let datasets = series
+        .iter()
+        .zip(&points)
+        .map(|((name, _, style), data)| {
+            Dataset::default()
+                .name(*name)
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(*style)
+                .data(data)
+        })
+        .collect();
+    let y_labels = [0.0, max / 2.0, max]
+        .iter()
+        .map(|v| Span::styled(format!("{:.1}", v), theme.text))
+        .collect();
+    let chart = Chart::new(datasets)
+        .block(titled_block(title, theme))
+        .x_axis(Axis::default().bounds([0.0, (window - 1) as f64]))
+        .y_axis(
+            Axis::default()
+                .style(theme.border)
+                .bounds([0.0, max])
+                .labels(y_labels),
+        );
+    f.render_widget(chart, area);
+}
+
+// Mirrors how tui lays out Table columns, returning the x offset and width of
+// each column so mouse clicks can be mapped back to them. The last column
+// runs to the edge of the area, which only widens its click target.
+fn table_columns(
+    area: Rect,
+    widths: &[Constraint],
+    spacing: u16,
+    symbol_width: u16,
+) -> Vec<(u16, u16)> {
+    let mut constraints = Vec::with_capacity(widths.len() * 2 + 1);
+    if symbol_width > 0 {
+        constraints.push(Constraint::Length(symbol_width));
+    }
+    for width in widths {
+        constraints.push(*width);
+        constraints.push(Constraint::Length(spacing));
+    }
+    constraints.pop();
+    let mut chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(constraints)
+        .split(Rect {
+            x: 0,
+            y: 0,
+            width: area.width,
+            height: 1,
+        });
+    if symbol_width > 0 {
+        chunks.remove(0);
+    }
+
+    let mut x = area.x + symbol_width;
+    let mut columns = Vec::with_capacity(widths.len());
+    for chunk in chunks.iter().step_by(2) {
+        columns.push((x, chunk.width));
+        x += chunk.width + spacing;
     }
-    return Span::styled(s, Style::default().fg(Color::LightGreen));
+    columns
+}
+
+// A rectangle of the given percentages centred in `area`
+fn centered(area: Rect, width_pct: u16, height_pct: u16) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage((100 - height_pct) / 2),
+                Constraint::Percentage(height_pct),
+                Constraint::Percentage((100 - height_pct) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - width_pct) / 2),
+                Constraint::Percentage(width_pct),
+                Constraint::Percentage((100 - width_pct) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(vertical[1])[1]
 }
 
 pub fn render_label_value<B: Backend>(
     f: &mut Frame<B>,
+    theme: &Theme,
     label: &str,
     value: String,
     label_chunk: Rect,
     value_chunk: Rect,
 ) {
-    let label_paragraph = Paragraph::new(Span::styled(label, Style::default()))
+    let label_paragraph = Paragraph::new(Span::styled(label, theme.text))
         .block(Block::default().borders(Borders::NONE))
         .alignment(Alignment::Left);
-    let value_paragraph = Paragraph::new(Span::styled(value, Style::default()))
+    let value_paragraph = Paragraph::new(Span::styled(value, theme.text))
         .block(Block::default().borders(Borders::NONE))
         .alignment(Alignment::Right);
     f.render_widget(label_paragraph, label_chunk);
@@ -37,26 +257,442 @@ pub fn render_label_value<B: Backend>(
 
 pub fn draw_ui<B: Backend>(
     f: &mut Frame<B>,
-    stats: &SystemStats,
-    disks: &DisksStats,
-    processes: &[ProcessInfo],
+    app: &mut App,
+    snapshot: &Snapshot,
+    alerts: &AlertEngine,
 ) {
-    // Main terminal frame
-    let main_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(28), Constraint::Percentage(72)].as_ref())
+    let outer_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(1), // tabs
+                Constraint::Min(0),    // body
+                Constraint::Length(1), // key hints
+            ]
+            .as_ref(),
+        )
         .split(f.size());
+    app.regions = Regions::default();
+    draw_tab_bar(f, app, outer_chunks[0]);
+    help::draw_footer(f, app, outer_chunks[2]);
+
+    let body = outer_chunks[1];
+    match app.tab {
+        Tab::Overview => {
+            let main_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(28), Constraint::Percentage(72)].as_ref())
+                .split(body);
+            create_stats_block(
+                f,
+                app,
+                &snapshot.stats,
+                &snapshot.core_breakdown,
+                &snapshot.disks,
+                &snapshot.pressure,
+                main_chunks[0],
+            );
+            draw_process_area(f, app, snapshot, alerts, main_chunks[1]);
+        }
+        Tab::Processes => draw_process_area(f, app, snapshot, alerts, body),
+        Tab::Cpu => cpu::draw_cpu_view(
+            f,
+            app,
+            &snapshot.stats,
+            snapshot.cpu_breakdown.as_ref(),
+            &snapshot.core_breakdown,
+            &snapshot.topology,
+            body,
+        ),
+        Tab::Memory => {
+            memory::draw_memory_view(f, app, &snapshot.stats, snapshot.meminfo.as_ref(), body)
+        }
+        Tab::Disks => disks::draw_disks_view(f, &app.theme, &snapshot.disks, body),
+        Tab::Network => network::draw_network_view(f, app, &snapshot.networks, body),
+        Tab::Sensors => sensors::draw_sensors_view(f, &app.theme, &snapshot.sensors, body),
+        Tab::Connections => connections::draw_connections_view(f, app, body),
+        Tab::Events => events::draw_events_view(f, app, body),
+    }
 
-    create_stats_block(f, stats, disks, main_chunks[0]);
-    create_processes_block(f, processes, main_chunks[1]);
+    if app.detail_pid.is_some() {
+        detail::draw_process_detail(f, app, &snapshot.processes, body);
+    }
+    if app.thread_view.is_some() {
+        threads::draw_threads(f, app, &snapshot.processes, body);
+    }
+    if app.show_help {
+        help::draw_help(f, app, body);
+    }
+}
+
+// Tabs pads each title with a space on both sides and separates them with a
+// one-column divider
+fn tab_rects(area: Rect, labels: &[String]) -> Vec<Rect> {
+    let mut x = area.x;
+    let mut rects = Vec::new();
+    for label in labels {
+        let width = label.chars().count() as u16;
+        rects.push(Rect {
+            x: x + 1,
+            y: area.y,
+            width,
+            height: 1,
+        });
+        x += width + 3;
+    }
+    rects
+}
+
+fn draw_tab_bar<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let theme = &app.theme;
+    let labels: Vec<String> = TABS
+        .iter()
+        .enumerate()
+        .map(|(i, t)| format!("{} {}", i + 1, t.title()))
+        .collect();
+
+    app.regions.tabs = tab_rects(area, &labels);
+
+    let titles = labels
+        .into_iter()
+        .map(|label| Spans::from(Span::styled(label, theme.text)))
+        .collect();
+    let tabs = Tabs::new(titles)
+        .select(app.tab.index())
+        .style(theme.border)
+        .highlight_style(theme.selection.patch(theme.title));
+    f.render_widget(tabs, area);
+
+    // Remind users why clicks stopped working after turning capture off,
+    // and which machine is showing when it is not this one
+    let mut notes = Vec::new();
+    if !app.mouse_capture {
+        notes.push(Span::styled("mouse off (m)", theme.warning));
+    }
+    if let Some(remote) = &app.remote {
+        if !notes.is_empty() {
+            notes.push(Span::raw("  "));
+        }
+        notes.push(remote_indicator(remote, theme));
+    }
+    if !notes.is_empty() {
+        let note = Paragraph::new(Spans::from(notes)).alignment(Alignment::Right);
+        f.render_widget(note, area);
+    }
+}
+
+fn remote_indicator(remote: &RemoteStatus, theme: &Theme) -> Span<'static> {
+    if remote.connected {
+        let host = remote.host.as_deref().unwrap_or("?");
+        return Span::styled(format!("{} via {}", host, remote.address), theme.header);
+    }
+    let last = match remote.last_seen {
+        Some(at) => format!(", last data {}s ago", at.elapsed().as_secs()),
+        None => String::new(),
+    };
+    let state = match &remote.error {
+        Some(error) => format!("disconnected ({}), retrying", error),
+        None => String::from("connecting"),
+    };
+    Span::styled(
+        format!("{}: {}{}", remote.address, state, last),
+        theme.critical,
+    )
+}
+
+// Processes or groups, with the pinned and alerts panels when configured
+fn draw_process_area<B: Backend>(
+    f: &mut Frame<B>,
+    app: &mut App,
+    snapshot: &Snapshot,
+    alerts: &AlertEngine,
+    area: Rect,
+) {
+    let theme = app.theme.clone();
+    // The alerts panel only takes space when rules are configured
+    let right_chunks = if alerts.alerts.is_empty() {
+        vec![area]
+    } else {
+        let errors = alerts.alerts.iter().filter(|a| a.error.is_some()).count();
+        let rows = alerts.alerts.len() + errors + alerts.last_error.is_some() as usize;
+        let alerts_height = (rows as u16 + 3).min(12);
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(alerts_height)].as_ref())
+            .split(area)
+    };
+    match app.process_view {
+        ProcessView::Processes if !app.pinned.entries.is_empty() => {
+            let pinned_height = (app.pinned.entries.len() as u16 + 5).min(14);
+            let process_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(pinned_height), Constraint::Min(0)].as_ref())
+                .split(right_chunks[0]);
+            create_pinned_block(f, app, process_chunks[0]);
+            create_processes_block(f, app, snapshot, process_chunks[1]);
+        }
+        ProcessView::Processes => create_processes_block(f, app, snapshot, right_chunks[0]),
+        ProcessView::Groups => create_groups_block(f, &theme, snapshot, right_chunks[0]),
+    }
+    if right_chunks.len() > 1 {
+        create_alerts_block(f, &theme, alerts, right_chunks[1]);
+    }
+}
+
+pub fn create_alerts_block<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    alerts: &AlertEngine,
+    chunk: Rect,
+) {
+    let firing = alerts
+        .alerts
+        .iter()
+        .filter(|a| matches!(a.state, AlertState::Firing(_)))
+        .count();
+    let title = format!("Alerts ({} firing)", firing);
+    let alerts_block = titled_block(title, theme);
+    f.render_widget(alerts_block.clone(), chunk);
+
+    let inner_area = alerts_block.inner(chunk);
+    let alerts_margined_chunk = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(3)
+        .constraints([Constraint::Percentage(100)].as_ref())
+        .split(inner_area)[0];
+
+    let now = Instant::now();
+    let mut rows = Vec::new();
+    for alert in &alerts.alerts {
+        let (state, since) = match alert.state {
+            AlertState::Ok => (Span::styled("OK", theme.ok), None),
+            AlertState::Pending(since) => (Span::styled("PENDING", theme.warning), Some(since)),
+            AlertState::Firing(since) => (
+                Span::styled("FIRING", theme.critical.add_modifier(Modifier::BOLD)),
+                Some(since),
+            ),
+        };
+        let since = match since {
+            Some(since) => format!("{}s", now.duration_since(since).as_secs()),
+            None => String::from("-"),
+        };
+        let message = match alert.state {
+            AlertState::Ok => String::new(),
+            _ => alert.message.clone(),
+        };
+        rows.push(Row::new(vec![
+            Cell::from(state),
+            Cell::from(alert.rule.name.clone()),
+            Cell::from(since),
+            Cell::from(message),
+        ]));
+        if let Some(error) = &alert.error {
+            rows.push(Row::new(vec![
+                Cell::from(Span::styled("ERROR", theme.critical)),
+                Cell::from(alert.rule.name.clone()),
+                Cell::from(""),
+                Cell::from(error.clone()),
+            ]));
+        }
+    }
+    if let Some(error) = &alerts.last_error {
+        rows.push(Row::new(vec![
+            Cell::from(Span::styled("ERROR", theme.critical)),
+            Cell::from(""),
+            Cell::from(""),
+            Cell::from(error.clone()),
+        ]));
+    }
+
+    let header = Row::new(vec!["State", "Rule", "For", "Details"]).style(theme.header);
+
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::NONE))
+        .widths(&[
+            Constraint::Percentage(10), // STATE
+            Constraint::Percentage(20), // RULE
+            Constraint::Percentage(8),  // FOR
+            Constraint::Percentage(62), // DETAILS
+        ])
+        .column_spacing(2);
+
+    f.render_widget(table, alerts_margined_chunk);
+}
+
+pub fn create_pinned_block<B: Backend>(f: &mut Frame<B>, app: &mut App, chunk: Rect) {
+    let theme = &app.theme;
+    let pinned = &app.pinned.entries;
+    let pinned_block = titled_block(String::from("Pinned"), theme);
+    f.render_widget(pinned_block.clone(), chunk);
+
+    let inner_area = pinned_block.inner(chunk);
+    let pinned_margined_chunk = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(3)
+        .vertical_margin(1)
+        .constraints([Constraint::Percentage(100)].as_ref())
+        .split(inner_area)[0];
+    let history_width = (pinned_margined_chunk.width as usize * 18 / 100).max(1);
+    app.regions.pinned_rows = Rect {
+        y: pinned_margined_chunk.y + 1,
+        height: pinned_margined_chunk.height.saturating_sub(1),
+        ..pinned_margined_chunk
+    };
+
+    let now = Instant::now();
+    let mut rows = Vec::new();
+    for entry in pinned {
+        let status = match entry.pid {
+            None => Span::styled("absent", theme.critical),
+            Some(_) if entry.recently_restarted(now) => Span::styled(
+                match entry.previous_pid {
+                    Some(old) => format!("restarted (was {})", old),
+                    None => String::from("restarted"),
+                },
+                theme.warning.add_modifier(Modifier::BOLD),
+            ),
+            Some(_) if entry.restarts > 0 => {
+                Span::raw(format!("running ({} restarts)", entry.restarts))
+            }
+            Some(_) => Span::raw("running"),
+        };
+        let pid = match entry.pid {
+            Some(pid) if entry.matches > 1 => format!("{} +{}", pid, entry.matches - 1),
+            Some(pid) => pid.to_string(),
+            None => String::from("-"),
+        };
+
+        rows.push(Row::new(vec![
+            Cell::from(entry.matcher.label()),
+            Cell::from(pid),
+            Cell::from(status),
+            Cell::from(format!(
+                "{:.2}%",
+                app.cpu_mode.scale(entry.cpu, app.cpu_count)
+            )),
+            Cell::from(Span::styled(
+                text_sparkline(&scaled_cpu(app, &entry.cpu_history), history_width, 10000),
+                theme.chart,
+            )),
+            Cell::from(format_mb(entry.memory)),
+            Cell::from(Span::styled(
+                text_sparkline(&entry.memory_history, history_width, 0),
+                theme.chart,
+            )),
+        ]));
+    }
+
+    let header = Row::new(vec![
+        "Pin",
+        "PID",
+        "Status",
+        "CPU",
+        "CPU History",
+        "Mem (MB)",
+        "Mem History",
+    ])
+    .style(theme.header);
+
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::NONE))
+        .widths(&[
+            Constraint::Percentage(15), // PIN
+            Constraint::Percentage(10), // PID
+            Constraint::Percentage(17), // STATUS
+            Constraint::Percentage(8),  // CPU
+            Constraint::Percentage(18), // CPU HISTORY
+            Constraint::Percentage(9),  // MEM (MB)
+            Constraint::Percentage(18), // MEM HISTORY
+        ])
+        .column_spacing(1);
+
+    f.render_widget(table, pinned_margined_chunk);
+}
+
+// Column widths as percentages, scaled so the shown columns fill the table
+fn process_widths(columns: &[SortColumn]) -> Vec<Constraint> {
+    let total: u16 = columns.iter().map(|c| c.weight()).sum();
+    columns
+        .iter()
+        .map(|c| Constraint::Percentage(c.weight() * 100 / total.max(1)))
+        .collect()
+}
+
+// Width of the inline history columns, in samples
+const INLINE_HISTORY: usize = 12;
+
+fn process_cell(app: &App, p: &ProcessInfo, column: SortColumn, total_memory: u64) -> String {
+    let series = app.process_history.get(p);
+    // smaps_rollup fields are missing for processes we may not read
+    let optional_mb = |value: Option<u64>| match value {
+        Some(bytes) => format_mb(bytes),
+        None => String::from("-"),
+    };
+    match column {
+        SortColumn::Pid => p.pid.to_string(),
+        SortColumn::Name => p.name.clone(),
+        SortColumn::Memory => format_mb(p.memory),
+        SortColumn::Cpu => format!("{:.2}%", p.cpu),
+        SortColumn::Uptime => p.uptime.to_string(),
+        SortColumn::User => match (&p.euid, p.egid) {
+            (Some(uid), Some(gid)) => format!("{:?}/{:?}", **uid, *gid),
+            (Some(uid), None) => format!("{:?} / N/A", **uid),
+            (None, Some(gid)) => format!("N/A / {:?}", *gid),
+            (None, None) => String::from("N/A"),
+        },
+        SortColumn::Group => match &p.cgroup {
+            Some(info) => info.label(),
+            None => String::from("-"),
+        },
+        SortColumn::Virtual => format_mb(p.virtual_memory),
+        SortColumn::Pss => optional_mb(p.pss),
+        SortColumn::Uss => optional_mb(p.uss),
+        SortColumn::Swap => optional_mb(p.swap),
+        SortColumn::State => p.state.to_string(),
+        // CPU is scaled to one core, memory to the recent maximum
+        SortColumn::CpuHistory => series
+            .map(|s| text_sparkline(&scaled_cpu(app, &s.cpu), INLINE_HISTORY, 10000))
+            .unwrap_or_default(),
+        SortColumn::MemoryHistory => series
+            .map(|s| text_sparkline(&s.memory, INLINE_HISTORY, 0))
+            .unwrap_or_default(),
+        SortColumn::MemoryPercent => {
+            let percent = p.memory as f64 / total_memory.max(1) as f64 * 100.0;
+            format!("{:.1}%", percent)
+        }
+    }
 }
 
 pub fn create_processes_block<B: Backend>(
     f: &mut Frame<B>,
-    processes: &[ProcessInfo],
+    app: &mut App,
+    snapshot: &Snapshot,
     chunk: Rect,
 ) {
-    let processes_block = Block::default().title("Processes").borders(Borders::ALL);
+    let processes = &snapshot.processes;
+    let theme = &app.theme;
+    let counts = StateCounts::count(processes);
+    // Disk sleep is only listed while something is stuck in it
+    let disk_sleep = if counts.disk_sleep > 0 {
+        format!("{} disk sleep, ", counts.disk_sleep)
+    } else {
+        String::new()
+    };
+    let processes_block = titled_block(
+        format!(
+            "Processes: {} running, {} sleeping, {}{} stopped, {} zombie, {} threads (CPU: {})",
+            counts.running,
+            counts.sleeping,
+            disk_sleep,
+            counts.stopped,
+            counts.zombie,
+            counts.threads,
+            app.cpu_mode.title()
+        ),
+        theme,
+    );
     f.render_widget(processes_block.clone(), chunk);
 
     let inner_area = processes_block.inner(chunk);
@@ -66,65 +702,180 @@ pub fn create_processes_block<B: Backend>(
         .vertical_margin(2)
         .constraints([Constraint::Percentage(100)].as_ref())
         .split(inner_area)[0];
+
+    // Keep the selected row inside the visible window (header takes 2 lines)
+    let visible = process_margined_chunk.height.saturating_sub(2).max(1) as usize;
+    let selected = app.selected_index(processes);
+    if let Some(selected) = selected {
+        if selected < app.process_scroll {
+            app.process_scroll = selected;
+        } else if selected >= app.process_scroll + visible {
+            app.process_scroll = selected + 1 - visible;
+        }
+    }
+    app.process_scroll = app
+        .process_scroll
+        .min(processes.len().saturating_sub(visible));
+
     let mut rows = Vec::new();
-    for p in processes {
-        let mem_mb = (p.memory as f64) / 1000000.0;
-        let euid_egid = match (p.euid.clone(), p.egid) {
-            (Some(_), Some(_)) => format!("{:?}/{:?}", *p.euid.clone().unwrap(), *p.egid.unwrap()),
-            (Some(_), None) => format!("{:?} / N/A", *p.euid.clone().unwrap()),
-            (None, Some(_)) => format!("N/A / {:?}", *p.egid.unwrap()),
-            (None, None) => String::from("N/A"),
+    for p in processes.iter().skip(app.process_scroll).take(visible) {
+        let cells = app
+            .columns
+            .iter()
+            .map(|column| process_cell(app, p, *column, snapshot.stats.total_memory));
+        // Stuck (uninterruptible) and defunct processes stand out
+        let style = match p.state {
+            'D' => theme.warning,
+            'Z' => theme.critical,
+            _ => Style::default(),
         };
+        rows.push(Row::new(cells.collect::<Vec<_>>()).style(style));
+    }
+
+    // Column Names, with an arrow on the sorted one
+    let header = Row::new(
+        app.columns
+            .iter()
+            .map(|column| {
+                if *column != app.sort_column {
+                    column.title().to_string()
+                } else if app.sort_descending {
+                    format!("{} ▼", column.title())
+                } else {
+                    format!("{} ▲", column.title())
+                }
+            })
+            .collect::<Vec<_>>(),
+    )
+    .style(theme.header)
+    .bottom_margin(1);
+
+    let widths = process_widths(&app.columns);
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::NONE))
+        .widths(&widths)
+        .column_spacing(2) // extra space between columns
+        .highlight_style(theme.selection)
+        .highlight_symbol(">>");
 
-        let row = Row::new(vec![
-            p.pid.to_string(),
-            p.name.clone(),
-            format!("{:.2}", mem_mb),
-            format!("{:.2}%", p.cpu * 100.0),
-            p.uptime.to_string(),
-            euid_egid,
-        ]);
+    let mut state = TableState::default();
+    state.select(selected.map(|i| i - app.process_scroll));
+    let symbol_width = if state.selected().is_some() { 2 } else { 0 };
+    app.regions.process_header = Rect {
+        height: 1,
+        ..process_margined_chunk
+    };
+    app.regions.process_columns = table_columns(process_margined_chunk, &widths, 2, symbol_width);
+    app.regions.process_rows = Rect {
+        y: process_margined_chunk.y + 2,
+        height: process_margined_chunk.height.saturating_sub(2),
+        ..process_margined_chunk
+    };
+    f.render_stateful_widget(table, process_margined_chunk, &mut state);
+}
+
+pub fn create_groups_block<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    snapshot: &Snapshot,
+    chunk: Rect,
+) {
+    let groups_block = titled_block(String::from("Groups"), theme);
+    f.render_widget(groups_block.clone(), chunk);
+
+    let inner_area = groups_block.inner(chunk);
+    let groups_margined_chunk = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(3)
+        .vertical_margin(2)
+        .constraints([Constraint::Percentage(100)].as_ref())
+        .split(inner_area)[0];
+    let mut rows = Vec::new();
+    for g in &snapshot.groups {
+        // Usage is coloured against the cgroup limit when one is set. The
+        // limit is a percentage of one core, so it is converted into the
+        // mode usage is shown in.
+        let cpu_str = format!("{:.2}%", g.cpu);
+        let cpus = snapshot.stats.cpu_cores.len();
+        let cpu_max = g
+            .limits
+            .cpu_max
+            .map(|max| snapshot.cpu_mode.scale(max, cpus));
+        let (cpu_cell, cpu_limit) = match cpu_max {
+            Some(max) => (
+                Cell::from(theme.severity(cpu_str, g.cpu / max * 100.0)),
+                format!("{:.0}%", max),
+            ),
+            None => (Cell::from(cpu_str), String::from("max")),
+        };
+        let (mem_cell, mem_limit) = match (g.limits.memory_current, g.limits.memory_max) {
+            (Some(current), Some(max)) => {
+                let pct = current as f64 / max as f64 * 100.0;
+                (
+                    Cell::from(
+                        theme.severity(format!("{} ({:.0}%)", format_mb(current), pct), pct as f32),
+                    ),
+                    format_mb(max),
+                )
+            }
+            (Some(current), None) => (Cell::from(format_mb(current)), String::from("max")),
+            (None, _) => (Cell::from(format_mb(g.memory)), String::from("-")),
+        };
 
-        rows.push(row);
+        rows.push(Row::new(vec![
+            Cell::from(g.info.label()),
+            Cell::from(g.num_processes.to_string()),
+            cpu_cell,
+            Cell::from(cpu_limit),
+            mem_cell,
+            Cell::from(mem_limit),
+            Cell::from(format_mb(g.read_bytes)),
+            Cell::from(format_mb(g.written_bytes)),
+        ]));
     }
 
-    // Column Names
     let header = Row::new(vec![
-        "PID",
-        "Name",
-        "Mem (MB)",
+        "Group",
+        "Procs",
         "CPU",
-        "Uptime (s)",
-        "EUID/EGID",
+        "CPU Limit",
+        "Mem (MB)",
+        "Mem Limit",
+        "Read (MB)",
+        "Written (MB)",
     ])
-    .style(Style::default().fg(Color::Yellow))
+    .style(theme.header)
     .bottom_margin(1);
 
     let table = Table::new(rows)
         .header(header)
         .block(Block::default().borders(Borders::NONE))
         .widths(&[
-            Constraint::Percentage(8),  // PID
-            Constraint::Percentage(32), // NAME
-            Constraint::Percentage(13), // MEM (MB)
-            Constraint::Percentage(10), // CPU
-            Constraint::Percentage(16), // UPTIME (s)
-            Constraint::Percentage(19), // EUID/EGID
+            Constraint::Percentage(26), // GROUP
+            Constraint::Percentage(6),  // PROCS
+            Constraint::Percentage(9),  // CPU
+            Constraint::Percentage(9),  // CPU LIMIT
+            Constraint::Percentage(15), // MEM
+            Constraint::Percentage(11), // MEM LIMIT
+            Constraint::Percentage(11), // READ
+            Constraint::Percentage(13), // WRITTEN
         ])
-        .column_spacing(2) // extra space between columns
-        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
-        .highlight_symbol(">>");
+        .column_spacing(2);
 
-    f.render_widget(table, process_margined_chunk);
+    f.render_widget(table, groups_margined_chunk);
 }
 
 pub fn create_stats_block<B: Backend>(
     f: &mut Frame<B>,
+    app: &mut App,
     stats: &SystemStats,
-    disks: &DisksStats,
+    core_breakdown: &[CpuBreakdown],
+    disks: &[DiskInfo],
+    pressure: &PressureStats,
     chunk: Rect,
 ) {
-    let block = Block::default().title("Stats").borders(Borders::ALL);
+    let block = titled_block(String::from("Stats"), &app.theme);
     f.render_widget(block, chunk);
 
     let sub_chunks = Layout::default()
@@ -135,20 +886,34 @@ pub fn create_stats_block<B: Backend>(
             [
                 Constraint::Percentage(10 + (stats.cpu_names.len() as u16 * 2)), // cpu
                 Constraint::Percentage(15),                                      // mem
-                Constraint::Percentage(50),                                      // disks
+                Constraint::Length(9),                                           // pressure
+                Constraint::Percentage(40),                                      // disks
                 Constraint::Percentage(10),                                      // system stats
             ]
             .as_ref(),
         )
         .split(chunk);
 
-    draw_cpu_section(f, stats, sub_chunks[0]);
-    draw_memory_section(f, stats, sub_chunks[1]);
-    draw_disk_section(f, &disks, sub_chunks[2]);
-    draw_system_section(f, stats, sub_chunks[3]);
+    app.regions.sections = vec![
+        (sub_chunks[0], Tab::Cpu),
+        (sub_chunks[1], Tab::Memory),
+        (sub_chunks[3], Tab::Disks),
+    ];
+    let theme = &app.theme;
+    draw_cpu_section(f, theme, stats, core_breakdown, sub_chunks[0]);
+    draw_memory_section(f, theme, stats, sub_chunks[1]);
+    draw_pressure_section(f, app, pressure, sub_chunks[2]);
+    draw_disk_section(f, theme, disks, sub_chunks[3]);
+    draw_system_section(f, theme, stats, sub_chunks[4]);
 }
 
-fn draw_cpu_section<B: Backend>(f: &mut Frame<B>, stats: &SystemStats, area: Rect) {
+fn draw_cpu_section<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    stats: &SystemStats,
+    core_breakdown: &[CpuBreakdown],
+    area: Rect,
+) {
     let block = Block::default().borders(Borders::NONE);
 
     f.render_widget(block, area);
@@ -163,13 +928,13 @@ fn draw_cpu_section<B: Backend>(f: &mut Frame<B>, stats: &SystemStats, area: Rec
     // GLOBAL CPU USAGE
     let usage_val = stats.cpu_global_usage;
     let usage_str = format!("{:.2}%", usage_val);
-    let usage_span = color_severity(usage_str, usage_val as f32);
+    let usage_span = theme.severity(usage_str, usage_val);
     let global_cpu_chunk = Layout::default()
         .direction(Direction::Horizontal)
         .margin(1)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(cpu_sub_chunks[0]);
-    let label_paragraph = Paragraph::new("Global CPU Usage: ")
+    let label_paragraph = Paragraph::new(Span::styled("Global CPU Usage: ", theme.text))
         .block(Block::default().borders(Borders::NONE))
         .alignment(Alignment::Left);
     f.render_widget(label_paragraph, global_cpu_chunk[0]);
@@ -198,17 +963,40 @@ fn draw_cpu_section<B: Backend>(f: &mut Frame<B>, stats: &SystemStats, area: Rec
 
     for i in 0..num_cpus {
         let cpu_name = format!("CPU {}", stats.cpu_names[i]);
-        render_label_value(
-            f,
-            &cpu_name,
-            format!("{:.2}%", stats.cpu_cores[i]),
+        let usage = format!("{:.2}%", stats.cpu_cores[i]);
+        let Some(breakdown) = core_breakdown.get(i) else {
+            render_label_value(
+                f,
+                theme,
+                &cpu_name,
+                usage,
+                indiv_cpus_label_chunk[i],
+                indiv_cpus_value_chunk[i],
+            );
+            continue;
+        };
+        // Stacked user/system/irq/iowait/steal bar ahead of the percentage
+        let value_chunk = indiv_cpus_value_chunk[i];
+        let bar_width = (value_chunk.width as usize).saturating_sub(usage.len() + 1);
+        let mut spans = cpu::breakdown_bar(theme, breakdown, bar_width);
+        spans.push(Span::styled(format!(" {}", usage), theme.text));
+        f.render_widget(
+            Paragraph::new(Span::styled(cpu_name, theme.text)),
             indiv_cpus_label_chunk[i],
-            indiv_cpus_value_chunk[i],
+        );
+        f.render_widget(
+            Paragraph::new(Spans::from(spans)).alignment(Alignment::Right),
+            value_chunk,
         );
     }
 }
 
-fn draw_memory_section<B: Backend>(f: &mut Frame<B>, stats: &SystemStats, area: Rect) {
+fn draw_memory_section<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    stats: &SystemStats,
+    area: Rect,
+) {
     let block = Block::default().borders(Borders::NONE);
 
     f.render_widget(block, area);
@@ -250,13 +1038,12 @@ fn draw_memory_section<B: Backend>(f: &mut Frame<B>, stats: &SystemStats, area:
         ])
         .split(mem_sub_chunks[1]);
 
-    let avail_mem = stats.total_memory - stats.used_memory;
     let mem_percentage_val = (stats.used_memory as f64 / stats.total_memory as f64) * 100.0;
     let mem_percentage_str = format!("{:.2}%", mem_percentage_val);
-    let colored_span = color_severity(mem_percentage_str, mem_percentage_val as f32);
+    let colored_span = theme.severity(mem_percentage_str, mem_percentage_val as f32);
 
     // Render the “Memory"
-    let label_paragraph = Paragraph::new("Memory: ")
+    let label_paragraph = Paragraph::new(Span::styled("Memory: ", theme.text))
         .block(Block::default().borders(Borders::NONE))
         .alignment(Alignment::Left);
     f.render_widget(label_paragraph, mem_label_subchunks[0]);
@@ -266,6 +1053,7 @@ fn draw_memory_section<B: Backend>(f: &mut Frame<B>, stats: &SystemStats, area:
     f.render_widget(value_paragraph, mem_num_subchunks[0]);
     render_label_value(
         f,
+        theme,
         "Total Memory: ",
         format!("{:.2} GB", (stats.total_memory as f64 / 1000000000.0)),
         mem_label_subchunks[2],
@@ -273,13 +1061,15 @@ fn draw_memory_section<B: Backend>(f: &mut Frame<B>, stats: &SystemStats, area:
     );
     render_label_value(
         f,
+        theme,
         "Avail Memory: ",
-        format!("{:.2} GB", (avail_mem as f64 / 1000000000.0)),
+        format!("{:.2} GB", (stats.available_memory as f64 / 1000000000.0)),
         mem_label_subchunks[3],
         mem_num_subchunks[3],
     );
     render_label_value(
         f,
+        theme,
         "Used Memory: ",
         format!("{:.2} GB", (stats.used_memory as f64 / 1000000000.0)),
         mem_label_subchunks[4],
@@ -287,6 +1077,7 @@ fn draw_memory_section<B: Backend>(f: &mut Frame<B>, stats: &SystemStats, area:
     );
     render_label_value(
         f,
+        theme,
         "Free Memory: ",
         format!("{:.2} MB", (stats.free_memory as f64 / 1000000.0)),
         mem_label_subchunks[5],
@@ -294,105 +1085,178 @@ fn draw_memory_section<B: Backend>(f: &mut Frame<B>, stats: &SystemStats, area:
     );
 }
 
-fn draw_disk_section<B: Backend>(f: &mut Frame<B>, disk_stats: &DisksStats, area: Rect) {
-    let num_disks = disk_stats.disk_names.len();
-    let constraints = vec![Constraint::Percentage(100 / (num_disks as u16)); num_disks];
-    let disk_chunks = Layout::default()
+fn pressure_spans(theme: &Theme, label: &str, pressure: &Option<Pressure>) -> Spans<'static> {
+    let mut spans = vec![Span::styled(format!("{:<5}", label), theme.text)];
+    match pressure {
+        Some(p) => {
+            spans.push(Span::raw("some "));
+            for value in [p.some.avg10, p.some.avg60, p.some.avg300] {
+                spans.push(theme.pressure(format!("{:>5.1}", value), value));
+            }
+            if let Some(full) = &p.full {
+                spans.push(Span::raw(" full "));
+                for value in [full.avg10, full.avg60, full.avg300] {
+                    spans.push(theme.pressure(format!("{:>5.1}", value), value));
+                }
+            }
+        }
+        None => spans.push(Span::styled("n/a", theme.text)),
+    }
+    Spans::from(spans)
+}
+
+fn draw_pressure_section<B: Backend>(
+    f: &mut Frame<B>,
+    app: &App,
+    pressure: &PressureStats,
+    area: Rect,
+) {
+    let theme = &app.theme;
+    let block = Block::default()
+        .title(Span::styled("Pressure (avg10/60/300)", theme.title))
+        .borders(Borders::NONE);
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+
+    if !pressure.available() {
+        let note =
+            Paragraph::new(Span::styled("PSI unavailable", theme.text)).alignment(Alignment::Left);
+        f.render_widget(note, inner_area);
+        return;
+    }
+
+    // One line of averages followed by sparklines of avg10 per resource, "some"
+    // on the left and "full" on the right when the kernel reports it
+    let rows = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
+        .constraints([Constraint::Length(1); 6].as_ref())
+        .split(inner_area);
+    let history = &app.pressure_history;
+    let resources = [
+        ("CPU", &pressure.cpu, &history.cpu),
+        ("Mem", &pressure.memory, &history.memory),
+        ("IO", &pressure.io, &history.io),
+    ];
+    for (i, (label, values, series)) in resources.into_iter().enumerate() {
+        f.render_widget(
+            Paragraph::new(pressure_spans(theme, label, values)),
+            rows[i * 2],
+        );
+        let has_full = values.as_ref().is_some_and(|p| p.full.is_some());
+        if has_full {
+            let halves = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                .split(rows[i * 2 + 1]);
+            draw_pressure_sparkline(f, theme, &series.some, halves[0]);
+            draw_pressure_sparkline(f, theme, &series.full, halves[1]);
+        } else {
+            draw_pressure_sparkline(f, theme, &series.some, rows[i * 2 + 1]);
+        }
+    }
+}
+
+fn draw_pressure_sparkline<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    samples: &VecDeque<u64>,
+    area: Rect,
+) {
+    // Only the newest samples that fit the width, scaled to at least 10%
+    let skip = samples.len().saturating_sub(area.width as usize);
+    let data: Vec<u64> = samples.iter().skip(skip).cloned().collect();
+    let max = data.iter().cloned().max().unwrap_or(0).max(1000);
+    let sparkline = Sparkline::default().data(&data).max(max).style(theme.chart);
+    f.render_widget(sparkline, area);
+}
+
+fn draw_disk_section<B: Backend>(f: &mut Frame<B>, theme: &Theme, disks: &[DiskInfo], area: Rect) {
+    if disks.is_empty() {
+        return;
+    }
+    // Seven rows plus borders per disk; disks that don't fit are cut off
+    let constraints = vec![Constraint::Length(9); disks.len()];
+    let disk_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .horizontal_margin(1)
         .constraints(constraints)
         .split(area);
 
-    for i in 0..num_disks {
-        let block = Block::default()
-            .title(format!("Disk {i}"))
-            .borders(Borders::ALL);
-        f.render_widget(block.clone(), disk_chunks[i]);
+    for (i, (disk, &disk_chunk)) in disks.iter().zip(disk_chunks.iter()).enumerate() {
+        let block = titled_block(format!("Disk {i}"), theme);
+        f.render_widget(block.clone(), disk_chunk);
 
         // Inner area for the disk block
-        let inner_area = block.inner(disk_chunks[i]);
+        let inner_area = block.inner(disk_chunk);
         let disk_sub_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
             .split(inner_area);
 
-        // Color-code disk usage
-        let usage_val = disk_stats.disk_usages[i].parse::<f32>().unwrap();
-        let usage_str = format!("{:.2}%", usage_val);
-        let usage_span = color_severity(usage_str, usage_val);
+        // Color-code disk and inode usage
+        let usage_val = disk.usage();
+        let usage_span = theme.severity(format!("{:.2}%", usage_val), usage_val);
+        let inodes_span = match &disk.inodes {
+            Some(inodes) => theme.severity(format!("{:.2}%", inodes.usage()), inodes.usage()),
+            None => Span::styled("n/a", theme.text),
+        };
+        let rows = [
+            (
+                "Mount Point: ",
+                Span::styled(disk.mount_point.clone(), theme.text),
+            ),
+            ("Name: ", Span::styled(disk.name.clone(), theme.text)),
+            ("Usage: ", usage_span),
+            (
+                "Size: ",
+                Span::styled(format_bytes(disk.total_space), theme.text),
+            ),
+            (
+                "Free: ",
+                Span::styled(format_bytes(disk.available_space), theme.text),
+            ),
+            ("Inodes: ", inodes_span),
+            (
+                "Filesystem: ",
+                Span::styled(
+                    format!("{} ({})", disk.file_system, disk.flags()),
+                    theme.text,
+                ),
+            ),
+        ];
 
-        // Left column label chunk
+        // Left column labels, right column values
         let label_col = Layout::default()
             .direction(Direction::Vertical)
-            .margin(1)
-            .constraints([
-                Constraint::Length(1), // Mount point
-                Constraint::Length(1), // Name
-                Constraint::Length(1), // Usage label
-                Constraint::Length(1), // Filesystem
-                Constraint::Length(1), // Kind
-            ])
+            .horizontal_margin(1)
+            .constraints(vec![Constraint::Length(1); rows.len()])
             .split(disk_sub_chunks[0]);
-
-        // Right column value chunk
         let value_col = Layout::default()
             .direction(Direction::Vertical)
-            .margin(1)
-            .constraints([
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(1),
-            ])
+            .horizontal_margin(1)
+            .constraints(vec![Constraint::Length(1); rows.len()])
             .split(disk_sub_chunks[1]);
 
-        render_label_value(
-            f,
-            "Mount Point: ",
-            disk_stats.disk_mnt_pts[i].clone(),
-            label_col[0],
-            value_col[0],
-        );
-
-        render_label_value(
-            f,
-            "Name: ",
-            disk_stats.disk_names[i].clone(),
-            label_col[1],
-            value_col[1],
-        );
-
-        // Color-code Usage
-        let label_usage = Paragraph::new("Usage: ")
-            .block(Block::default().borders(Borders::NONE))
-            .alignment(Alignment::Left);
-        f.render_widget(label_usage, label_col[2]);
-
-        let usage_par = Paragraph::new(usage_span)
-            .block(Block::default().borders(Borders::NONE))
-            .alignment(Alignment::Right);
-        f.render_widget(usage_par, value_col[2]);
-
-        render_label_value(
-            f,
-            "Filesystem: ",
-            disk_stats.disk_filesystems[i].clone(),
-            label_col[3],
-            value_col[3],
-        );
-
-        render_label_value(
-            f,
-            "Kind: ",
-            disk_stats.disk_kinds[i].clone(),
-            label_col[4],
-            value_col[4],
-        );
+        for (row, (label, value)) in rows.into_iter().enumerate() {
+            let label_paragraph = Paragraph::new(Span::styled(label, theme.text))
+                .block(Block::default().borders(Borders::NONE))
+                .alignment(Alignment::Left);
+            f.render_widget(label_paragraph, label_col[row]);
+            let value_paragraph = Paragraph::new(value)
+                .block(Block::default().borders(Borders::NONE))
+                .alignment(Alignment::Right);
+            f.render_widget(value_paragraph, value_col[row]);
+        }
     }
 }
 
-fn draw_system_section<B: Backend>(f: &mut Frame<B>, stats: &SystemStats, area: Rect) {
+fn draw_system_section<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    stats: &SystemStats,
+    area: Rect,
+) {
     let block = Block::default().borders(Borders::NONE);
     f.render_widget(block, area);
 
@@ -431,20 +1295,29 @@ fn draw_system_section<B: Backend>(f: &mut Frame<B>, stats: &SystemStats, area:
 
     render_label_value(
         f,
+        theme,
         "Hostname: ",
-        stats.host_name.clone().unwrap(),
+        stats
+            .host_name
+            .clone()
+            .unwrap_or_else(|| String::from("unknown")),
         sys_label_subchunks[0],
         sys_num_subchunks[0],
     );
     render_label_value(
         f,
+        theme,
         "Version: ",
-        stats.os_version.clone().unwrap(),
+        stats
+            .os_version
+            .clone()
+            .unwrap_or_else(|| String::from("unknown")),
         sys_label_subchunks[1],
         sys_num_subchunks[1],
     );
     render_label_value(
         f,
+        theme,
         "Uptime: ",
         stats.uptime.to_string(),
         sys_label_subchunks[2],
@@ -452,6 +1325,7 @@ fn draw_system_section<B: Backend>(f: &mut Frame<B>, stats: &SystemStats, area:
     );
     render_label_value(
         f,
+        theme,
         "CPU_Arch: ",
         stats.arch.to_string(),
         sys_label_subchunks[3],
@@ -459,8 +1333,12 @@ fn draw_system_section<B: Backend>(f: &mut Frame<B>, stats: &SystemStats, area:
     );
     render_label_value(
         f,
+        theme,
         "OS: ",
-        stats.os_name.clone().unwrap(),
+        stats
+            .os_name
+            .clone()
+            .unwrap_or_else(|| String::from("unknown")),
         sys_label_subchunks[4],
         sys_num_subchunks[4],
     );
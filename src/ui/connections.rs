@@ -0,0 +1,112 @@
+use super::titled_block;
+use crate::app::App;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+pub fn draw_connections_view<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let theme = &app.theme;
+    let view = &app.connections;
+    let visible_connections = view.visible();
+    // Sockets are read from this machine's /proc, which says nothing about
+    // a remote host
+    let title = if app.remote.is_some() {
+        String::from("Connections (not available for a remote host)")
+    } else {
+        format!(
+            "Connections ({} of {})",
+            visible_connections.len(),
+            view.connections.len()
+        )
+    };
+    let block = titled_block(title, theme);
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .horizontal_margin(3)
+        .vertical_margin(1)
+        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(inner_area);
+
+    // Keep the selected row inside the visible window (header takes 2 lines)
+    let visible = chunks[0].height.saturating_sub(2).max(1) as usize;
+    let selected = view.selected_index(&visible_connections);
+    let mut scroll = view.scroll;
+    if let Some(selected) = selected {
+        if selected < scroll {
+            scroll = selected;
+        } else if selected >= scroll + visible {
+            scroll = selected + 1 - visible;
+        }
+    }
+    scroll = scroll.min(visible_connections.len().saturating_sub(visible));
+
+    let rows: Vec<Row> = visible_connections
+        .iter()
+        .skip(scroll)
+        .take(visible)
+        .map(|c| {
+            let owner = match c.pid {
+                Some(pid) => format!("{} ({})", c.process, pid),
+                None => String::from("-"),
+            };
+            let state = if c.socket.state == "LISTEN" {
+                Span::styled(c.socket.state, theme.header)
+            } else {
+                Span::styled(c.socket.state, theme.text)
+            };
+            Row::new(vec![
+                Cell::from(c.socket.protocol),
+                Cell::from(c.socket.local.clone()),
+                Cell::from(c.socket.remote.clone()),
+                Cell::from(state),
+                Cell::from(owner),
+            ])
+        })
+        .collect();
+
+    let header = Row::new(vec!["Protocol", "Local", "Remote", "State", "Process"])
+        .style(theme.header)
+        .bottom_margin(1);
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::NONE))
+        .widths(&[
+            Constraint::Length(8),      // PROTOCOL
+            Constraint::Percentage(25), // LOCAL
+            Constraint::Percentage(25), // REMOTE
+            Constraint::Length(12),     // STATE
+            Constraint::Percentage(30), // PROCESS
+        ])
+        .column_spacing(2)
+        .style(theme.text)
+        .highlight_style(theme.selection)
+        .highlight_symbol(">>");
+
+    let mut state = TableState::default();
+    state.select(selected.map(|i| i - scroll));
+    let scroll_rows = Rect {
+        y: chunks[0].y + 2,
+        height: chunks[0].height.saturating_sub(2),
+        ..chunks[0]
+    };
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    // A port number matches either end; other text matches any column
+    let search = &view.search;
+    let line = if search.editing {
+        Span::styled(format!("/{}_", search.query), theme.header)
+    } else if !search.query.is_empty() {
+        Span::styled(format!("/{}", search.query), theme.header)
+    } else {
+        Span::styled("/ to filter by port, state or process", theme.text)
+    };
+    f.render_widget(Paragraph::new(Spans::from(line)), chunks[1]);
+    app.connections.scroll = scroll;
+    app.regions.connection_rows = scroll_rows;
+}
@@ -0,0 +1,226 @@
+use super::{draw_history_chart, stacked_bar, text_sparkline, titled_block};
+use crate::app::App;
+use crate::system::{CpuBreakdown, SystemStats};
+use crate::theme::Theme;
+use crate::topology::{CpuTopology, LogicalCpu};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Span, Spans},
+    widgets::Paragraph,
+    Frame,
+};
+
+// Width of one "cpuN  xx.xx%  ██▓▒··  ▁▂▃" cell in the per-core grid
+const CORE_CELL_WIDTH: u16 = 52;
+const CORE_BAR_WIDTH: usize = 12;
+
+// How CPU time divides, as (label, percent, style, fill character).
+// Nice counts as user time and softirq as irq.
+fn breakdown_segments(theme: &Theme, b: &CpuBreakdown) -> [(&'static str, f32, Style, char); 6] {
+    [
+        ("user", b.user + b.nice, theme.ok, '█'),
+        ("system", b.system, theme.critical, '█'),
+        ("irq", b.irq + b.softirq, theme.header, '▓'),
+        ("iowait", b.iowait, theme.warning, '▒'),
+        ("steal", b.steal, theme.critical, '░'),
+        ("idle", b.idle, theme.text, '·'),
+    ]
+}
+
+pub fn breakdown_bar(theme: &Theme, b: &CpuBreakdown, width: usize) -> Vec<Span<'static>> {
+    let segments: Vec<(f64, Style, char)> = breakdown_segments(theme, b)
+        .iter()
+        .map(|(_, percent, style, fill)| (*percent as f64, *style, *fill))
+        .collect();
+    stacked_bar(&segments, 100.0, width)
+}
+
+// Every busy state with its share; idle is whatever the bar leaves blank
+fn breakdown_legend(theme: &Theme, b: &CpuBreakdown) -> Spans<'static> {
+    let mut spans = Vec::new();
+    for (label, percent, style, fill) in breakdown_segments(theme, b).iter().take(5) {
+        spans.push(Span::styled(format!("{} ", fill), *style));
+        spans.push(Span::styled(
+            format!("{} {:.1}%   ", label, percent),
+            theme.text,
+        ));
+    }
+    Spans::from(spans)
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+fn frequency_range(cpu: &LogicalCpu) -> String {
+    match (cpu.min_frequency, cpu.max_frequency) {
+        (Some(min), Some(max)) => format!("{}-{} MHz", min, max),
+        _ => String::from("-"),
+    }
+}
+
+fn topology_row(theme: &Theme, heading: String, cpu: &LogicalCpu) -> Spans<'static> {
+    Spans::from(vec![
+        Span::styled(format!("{:<10}", heading), theme.text),
+        Span::styled(format!("{:<8}", cpu.name), theme.text),
+        theme.severity(format!("{:>7.2}%", cpu.usage), cpu.usage),
+        Span::styled(
+            format!("{:>12}", format!("{} MHz", cpu.frequency)),
+            theme.text,
+        ),
+        Span::styled(format!("{:>18}  ", frequency_range(cpu)), theme.text),
+        Span::styled(
+            cpu.governor.clone().unwrap_or_else(|| String::from("-")),
+            theme.text,
+        ),
+    ])
+}
+
+// Logical CPUs grouped by socket, then by the physical core they share
+fn draw_topology<B: Backend>(f: &mut Frame<B>, app: &mut App, topology: &CpuTopology, area: Rect) {
+    let theme = &app.theme;
+    let block = titled_block(
+        format!(
+            "Topology: {} ({}), {}, {}, {}",
+            topology.brand,
+            topology.vendor,
+            plural(topology.sockets(), "socket"),
+            plural(topology.physical_cores(), "core"),
+            plural(topology.cpus.len(), "thread"),
+        ),
+        theme,
+    );
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .horizontal_margin(1)
+        .constraints([Constraint::Length(2), Constraint::Min(0)].as_ref())
+        .split(inner_area);
+
+    let header = format!(
+        "{:<10}{:<8}{:>8}{:>12}{:>18}  {}",
+        "Core", "CPU", "Usage", "Frequency", "Scaling", "Governor"
+    );
+    f.render_widget(
+        Paragraph::new(Span::styled(header, theme.header)),
+        chunks[0],
+    );
+
+    let mut lines = Vec::new();
+    for (package, cores) in topology.grouped() {
+        let socket = match package {
+            Some(id) => format!("Socket {}", id),
+            None => String::from("Socket ?"),
+        };
+        lines.push(Spans::from(Span::styled(socket, theme.header)));
+        for (core, cpus) in cores {
+            for (i, cpu) in cpus.iter().enumerate() {
+                // Only the first thread of a core repeats its id
+                let heading = match (i, core) {
+                    (0, Some(id)) => format!("  {}", id),
+                    (0, None) => String::from("  ?"),
+                    _ => String::new(),
+                };
+                lines.push(topology_row(theme, heading, cpu));
+            }
+        }
+    }
+
+    let visible = chunks[1].height as usize;
+    let scroll = app.topology_scroll.min(lines.len().saturating_sub(visible));
+    f.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), chunks[1]);
+    app.topology_scroll = scroll;
+}
+
+pub fn draw_cpu_view<B: Backend>(
+    f: &mut Frame<B>,
+    app: &mut App,
+    stats: &SystemStats,
+    breakdown: Option<&CpuBreakdown>,
+    core_breakdown: &[CpuBreakdown],
+    topology: &CpuTopology,
+    area: Rect,
+) {
+    let theme = &app.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)].as_ref())
+        .split(area);
+
+    draw_history_chart(
+        f,
+        theme,
+        format!("Global CPU ({:.2}%)", stats.cpu_global_usage),
+        &[("CPU", &app.history.cpu, theme.chart)],
+        100.0,
+        100.0,
+        chunks[0],
+    );
+
+    if app.show_topology {
+        return draw_topology(f, app, topology, chunks[1]);
+    }
+    let block = titled_block(format!("Cores ({})", stats.cpu_cores.len()), theme);
+    let mut inner_area = block.inner(chunks[1]);
+    f.render_widget(block, chunks[1]);
+    if stats.cpu_cores.is_empty() || inner_area.height == 0 {
+        return;
+    }
+
+    // Overall time breakdown above the grid
+    if let Some(breakdown) = breakdown {
+        let legend_area = Rect {
+            x: inner_area.x + 1,
+            height: 1,
+            width: inner_area.width.saturating_sub(2),
+            ..inner_area
+        };
+        f.render_widget(
+            Paragraph::new(breakdown_legend(theme, breakdown)),
+            legend_area,
+        );
+        inner_area.y += 2;
+        inner_area.height = inner_area.height.saturating_sub(2);
+    }
+
+    // Fill columns top to bottom, adding columns as the width allows
+    let columns = (inner_area.width / CORE_CELL_WIDTH).max(1) as usize;
+    let rows = stats.cpu_cores.len().div_ceil(columns);
+    let column_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(1)
+        .constraints(vec![Constraint::Ratio(1, columns as u32); columns])
+        .split(inner_area);
+    for (column, chunk) in column_chunks.iter().enumerate() {
+        let spark_width = (chunk.width as usize)
+            .saturating_sub(20 + CORE_BAR_WIDTH)
+            .max(1);
+        let lines: Vec<Spans> = (column * rows..((column + 1) * rows).min(stats.cpu_cores.len()))
+            .map(|i| {
+                let usage = stats.cpu_cores[i];
+                let history = match app.history.cores.get(i) {
+                    Some(samples) => text_sparkline(samples, spark_width, 10000),
+                    None => String::new(),
+                };
+                let mut spans = vec![
+                    Span::styled(format!("{:<7}", stats.cpu_names[i]), theme.text),
+                    theme.severity(format!("{:>7.2}% ", usage), usage),
+                ];
+                if let Some(breakdown) = core_breakdown.get(i) {
+                    spans.extend(breakdown_bar(theme, breakdown, CORE_BAR_WIDTH - 2));
+                    spans.push(Span::raw("  "));
+                }
+                spans.push(Span::styled(history, theme.chart));
+                Spans::from(spans)
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines), *chunk);
+    }
+}
@@ -0,0 +1,301 @@
+use super::{
+    centered, draw_history_chart, format_bytes, format_mb, scaled_cpu, tab_rects, titled_block,
+};
+use crate::app::{App, DetailTab, Inspection, DETAIL_TABS};
+use crate::processes::ProcessInfo;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph, Row, Table, Tabs, Wrap},
+    Frame,
+};
+
+// Header, column widths and rows of a list tab, before filtering
+type ListTab = (Vec<&'static str>, Vec<Constraint>, Vec<Vec<String>>);
+
+fn list_tab(inspection: &Inspection) -> ListTab {
+    match inspection.tab {
+        DetailTab::Info => (Vec::new(), Vec::new(), Vec::new()),
+        DetailTab::Files => (
+            vec!["FD", "Type", "Target"],
+            vec![
+                Constraint::Length(6),      // FD
+                Constraint::Length(7),      // TYPE
+                Constraint::Percentage(80), // TARGET
+            ],
+            inspection
+                .files
+                .iter()
+                .map(|f| vec![f.fd.to_string(), f.kind.to_string(), f.target.clone()])
+                .collect(),
+        ),
+        DetailTab::Sockets => (
+            vec!["Protocol", "Local", "Remote", "State"],
+            vec![
+                Constraint::Length(14),     // PROTOCOL
+                Constraint::Percentage(35), // LOCAL
+                Constraint::Percentage(35), // REMOTE
+                Constraint::Length(13),     // STATE
+            ],
+            inspection
+                .sockets
+                .iter()
+                .map(|s| {
+                    vec![
+                        s.protocol.to_string(),
+                        s.local.clone(),
+                        s.remote.clone(),
+                        s.state.to_string(),
+                    ]
+                })
+                .collect(),
+        ),
+        DetailTab::Maps => (
+            vec!["Address", "Perms", "RSS", "PSS", "Mapping"],
+            vec![
+                Constraint::Length(25),     // ADDRESS
+                Constraint::Length(5),      // PERMS
+                Constraint::Length(10),     // RSS
+                Constraint::Length(10),     // PSS
+                Constraint::Percentage(50), // MAPPING
+            ],
+            inspection
+                .maps
+                .iter()
+                .map(|m| {
+                    vec![
+                        m.range.clone(),
+                        m.perms.clone(),
+                        format_bytes(m.rss),
+                        format_bytes(m.pss),
+                        m.path.clone(),
+                    ]
+                })
+                .collect(),
+        ),
+    }
+}
+
+// A row matches when any cell contains the query, ignoring case
+fn matches(row: &[String], query: &str) -> bool {
+    let query = query.to_lowercase();
+    row.iter().any(|cell| cell.to_lowercase().contains(&query))
+}
+
+fn draw_list_tab<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let theme = &app.theme;
+    let inspection = &mut app.inspection;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(area);
+
+    if let Some(error) = &inspection.error {
+        let message = Span::styled(error.clone(), theme.warning);
+        f.render_widget(Paragraph::new(message), chunks[0]);
+        return;
+    }
+
+    let (header, widths, rows) = list_tab(inspection);
+    let total = rows.len();
+    let rows: Vec<Vec<String>> = rows
+        .into_iter()
+        .filter(|row| matches(row, &inspection.search.query))
+        .collect();
+
+    // Header takes 2 lines
+    let visible = chunks[0].height.saturating_sub(2).max(1) as usize;
+    inspection.scroll = inspection.scroll.min(rows.len().saturating_sub(visible));
+    let shown = rows.len();
+    let table = Table::new(
+        rows.into_iter()
+            .skip(inspection.scroll)
+            .take(visible)
+            .map(Row::new),
+    )
+    .header(Row::new(header).style(theme.header).bottom_margin(1))
+    .block(Block::default().borders(Borders::NONE))
+    .widths(&widths)
+    .column_spacing(1)
+    .style(theme.text);
+    f.render_widget(table, chunks[0]);
+
+    // Counts, the memory totals on Maps, and the search line
+    let mut status = vec![Span::styled(format!("{} of {}", shown, total), theme.text)];
+    if let (DetailTab::Maps, Some(rollup)) = (inspection.tab, &inspection.rollup) {
+        status.push(Span::styled(
+            format!(
+                "  total RSS {}  PSS {}  swap {}",
+                format_bytes(rollup.rss),
+                format_bytes(rollup.pss),
+                format_bytes(rollup.swap)
+            ),
+            theme.text,
+        ));
+    }
+    if inspection.search.editing {
+        status.push(Span::styled(
+            format!("  /{}_", inspection.search.query),
+            theme.header,
+        ));
+    } else if !inspection.search.query.is_empty() {
+        status.push(Span::styled(
+            format!("  /{}", inspection.search.query),
+            theme.header,
+        ));
+    }
+    f.render_widget(Paragraph::new(Spans::from(status)), chunks[1]);
+}
+
+fn detail_lines(app: &App, p: &ProcessInfo) -> Vec<Spans<'static>> {
+    let theme = &app.theme;
+    let user = match (&p.euid, p.egid) {
+        (Some(uid), Some(gid)) => format!("{}/{}", **uid, *gid),
+        (Some(uid), None) => format!("{} / N/A", **uid),
+        (None, Some(gid)) => format!("N/A / {}", *gid),
+        (None, None) => String::from("N/A"),
+    };
+    let watched = match app.process_history.get(p) {
+        Some(series) => format!("{}s", series.since.elapsed().as_secs()),
+        None => String::from("-"),
+    };
+    let mut fields = vec![
+        ("PID", p.pid.to_string()),
+        ("Name", p.name.clone()),
+        ("CPU", format!("{:.2}%", p.cpu)),
+        ("State", p.state.to_string()),
+        ("Virtual", format_bytes(p.virtual_memory)),
+        ("RSS", format_bytes(p.memory)),
+        ("Uptime", format!("{}s", p.uptime)),
+        ("Watched for", watched),
+        ("EUID/EGID", user),
+        ("Disk read", format_bytes(p.read_bytes)),
+        ("Disk written", format_bytes(p.written_bytes)),
+    ];
+    // Shared pages are split between their users in PSS and left out of USS
+    if let Some(rollup) = &app.inspection.rollup {
+        fields.push(("PSS", format_bytes(rollup.pss)));
+        fields.push(("USS", format_bytes(rollup.uss)));
+        fields.push(("Swap", format_bytes(rollup.swap)));
+    }
+    if let Some(cgroup) = &p.cgroup {
+        fields.push(("Cgroup", cgroup.path.clone()));
+        fields.push(("Group", cgroup.label()));
+    }
+    // Last, since a long command line wraps over several rows
+    fields.push(("Command", p.cmd.clone()));
+    fields
+        .into_iter()
+        .map(|(label, value)| {
+            Spans::from(vec![
+                Span::styled(format!("{:<14}", label), theme.header),
+                Span::styled(value, theme.text),
+            ])
+        })
+        .collect()
+}
+
+pub fn draw_process_detail<B: Backend>(
+    f: &mut Frame<B>,
+    app: &mut App,
+    processes: &[ProcessInfo],
+    area: Rect,
+) {
+    let popup = centered(area, 80, 70);
+    app.regions.detail = popup;
+    let theme = &app.theme;
+
+    let process = processes.iter().find(|p| Some(p.pid) == app.detail_pid);
+    let (title, lines) = match process {
+        Some(p) => (
+            format!("Process {} ({}) - Esc to close", p.pid, p.name),
+            detail_lines(app, p),
+        ),
+        None => (
+            String::from("Process - Esc to close"),
+            vec![Spans::from(Span::styled(
+                "The process has exited",
+                theme.warning,
+            ))],
+        ),
+    };
+
+    let block = titled_block(title, theme);
+    let inner_area = block.inner(popup);
+    let content = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(2)
+        .vertical_margin(1)
+        .constraints([Constraint::Percentage(100)].as_ref())
+        .split(inner_area)[0];
+    f.render_widget(Clear, popup);
+    f.render_widget(block, popup);
+    if process.is_none() {
+        f.render_widget(Paragraph::new(lines), content);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(0)].as_ref())
+        .split(content);
+    let labels: Vec<String> = DETAIL_TABS.iter().map(|t| t.title().to_string()).collect();
+    app.regions.detail_tabs = tab_rects(chunks[0], &labels);
+    let titles = labels
+        .into_iter()
+        .map(|label| Spans::from(Span::styled(label, theme.text)))
+        .collect();
+    let tabs = Tabs::new(titles)
+        .select(app.inspection.tab.index())
+        .style(theme.border)
+        .highlight_style(theme.selection.patch(theme.title));
+    f.render_widget(tabs, chunks[0]);
+
+    let series = process.and_then(|p| app.process_history.get(p));
+    match (app.inspection.tab, series) {
+        // CPU and memory since the process was first seen, under the fields
+        (DetailTab::Info, Some(series)) => {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(10)].as_ref())
+                .split(chunks[1]);
+            f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), rows[0]);
+            let charts = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                .split(rows[1]);
+            let scale = |cpu: f32| app.cpu_mode.scale(cpu, app.cpu_count);
+            draw_history_chart(
+                f,
+                theme,
+                format!(
+                    "CPU % (peak {:.1}, avg {:.1})",
+                    scale(series.peak_cpu),
+                    scale(series.average_cpu())
+                ),
+                &[("CPU", &scaled_cpu(app, &series.cpu), theme.chart)],
+                100.0,
+                100.0,
+                charts[0],
+            );
+            draw_history_chart(
+                f,
+                theme,
+                format!(
+                    "RSS MB (peak {}, avg {})",
+                    format_mb(series.peak_memory),
+                    format_mb(series.average_memory())
+                ),
+                &[("RSS", &series.memory, theme.chart)],
+                1000000.0,
+                0.0,
+                charts[1],
+            );
+        }
+        (DetailTab::Info, None) => {
+            f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), chunks[1])
+        }
+        _ => draw_list_tab(f, app, chunks[1]),
+    }
+}
@@ -0,0 +1,107 @@
+use super::{format_bytes, titled_block};
+use crate::disks::DiskInfo;
+use crate::theme::Theme;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::Span,
+    widgets::{Block, Borders, Cell, Row, Table},
+    Frame,
+};
+
+const BAR_WIDTH: usize = 20;
+
+fn usage_bar(usage: f32) -> String {
+    let filled = ((usage / 100.0 * BAR_WIDTH as f32).round() as usize).min(BAR_WIDTH);
+    format!("[{}{}]", "|".repeat(filled), " ".repeat(BAR_WIDTH - filled))
+}
+
+pub fn draw_disks_view<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    disks: &[DiskInfo],
+    area: Rect,
+) {
+    let block = titled_block(format!("Disks ({})", disks.len()), theme);
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+    let disks_margined_chunk = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(3)
+        .vertical_margin(1)
+        .constraints([Constraint::Percentage(100)].as_ref())
+        .split(inner_area)[0];
+
+    // Disks come sorted by physical device; each device gets a heading row
+    // with its partitions indented below it
+    let mut rows = Vec::new();
+    let mut current_device = None;
+    for disk in disks {
+        let indent = if disk.device.is_some() { "  " } else { "" };
+        if disk.device.is_some() && disk.device != current_device {
+            let device = disk.device.clone().unwrap_or_default();
+            rows.push(Row::new(vec![Cell::from(Span::styled(
+                device,
+                theme.header,
+            ))]));
+            current_device = disk.device.clone();
+        }
+        let mount_point = match disk.bind_mounts.len() {
+            0 => format!("{}{}", indent, disk.mount_point),
+            n => format!("{}{} (+{})", indent, disk.mount_point, n),
+        };
+        let usage = disk.usage();
+        let inodes = match &disk.inodes {
+            Some(inodes) => {
+                Cell::from(theme.severity(format!("{:.2}%", inodes.usage()), inodes.usage()))
+            }
+            None => Cell::from(Span::styled("n/a", theme.text)),
+        };
+        rows.push(Row::new(vec![
+            Cell::from(mount_point),
+            Cell::from(disk.name.clone()),
+            Cell::from(disk.file_system.clone()),
+            Cell::from(disk.flags()),
+            Cell::from(format_bytes(disk.used_space)),
+            Cell::from(format_bytes(disk.available_space)),
+            Cell::from(format_bytes(disk.total_space)),
+            Cell::from(theme.severity(format!("{:.2}%", usage), usage)),
+            Cell::from(theme.severity(usage_bar(usage), usage)),
+            inodes,
+        ]));
+    }
+
+    let header = Row::new(vec![
+        "Mount Point",
+        "Name",
+        "Filesystem",
+        "Kind",
+        "Used",
+        "Avail",
+        "Total",
+        "Usage",
+        "",
+        "Inodes",
+    ])
+    .style(theme.header)
+    .bottom_margin(1);
+
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::NONE))
+        .widths(&[
+            Constraint::Percentage(17), // MOUNT POINT
+            Constraint::Percentage(11), // NAME
+            Constraint::Percentage(7),  // FILESYSTEM
+            Constraint::Percentage(10), // KIND
+            Constraint::Percentage(8),  // USED
+            Constraint::Percentage(8),  // AVAIL
+            Constraint::Percentage(8),  // TOTAL
+            Constraint::Percentage(6),  // USAGE
+            Constraint::Length(BAR_WIDTH as u16 + 2),
+            Constraint::Percentage(12), // INODES
+        ])
+        .column_spacing(1);
+
+    f.render_widget(table, disks_margined_chunk);
+}
@@ -0,0 +1,96 @@
+use super::{format_bytes, titled_block};
+use crate::app::App;
+use crate::events::EventKind;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    text::Span,
+    widgets::{Cell, Row, Table},
+    Frame,
+};
+
+// HH:MM:SS in the local time zone
+pub fn clock(timestamp: u64) -> String {
+    let time = timestamp as libc::time_t;
+    // SAFETY: localtime_r only writes to the tm we pass and reads `time`
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return String::from("--:--:--");
+    }
+    format!("{:02}:{:02}:{:02}", tm.tm_hour, tm.tm_min, tm.tm_sec)
+}
+
+// Newest first, so fresh events show without scrolling
+pub fn draw_events_view<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let theme = &app.theme;
+    let log = &app.events;
+    let title = match &log.last_error {
+        Some(error) => format!("Events ({}) - {}", log.events.len(), error),
+        None => format!("Events ({})", log.events.len()),
+    };
+    let block = titled_block(title, theme);
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+    let table_area = Rect {
+        x: inner_area.x + 3,
+        y: inner_area.y + 1,
+        width: inner_area.width.saturating_sub(6),
+        height: inner_area.height.saturating_sub(1),
+    };
+
+    let visible = table_area.height.saturating_sub(2).max(1) as usize;
+    let scroll = app
+        .events_scroll
+        .min(log.events.len().saturating_sub(visible));
+    let rows: Vec<Row> = log
+        .events
+        .iter()
+        .rev()
+        .skip(scroll)
+        .take(visible)
+        .map(|e| {
+            let kind = match e.kind {
+                EventKind::Start => Span::styled(e.kind.title(), theme.ok),
+                EventKind::Exit => Span::styled(e.kind.title(), theme.warning),
+            };
+            let parent = match e.parent {
+                Some(pid) => pid.to_string(),
+                None => String::from("-"),
+            };
+            let lifetime = match e.lifetime {
+                Some(secs) => format!("{}s", secs),
+                None => String::from("-"),
+            };
+            Row::new(vec![
+                Cell::from(clock(e.timestamp)),
+                Cell::from(kind),
+                Cell::from(e.pid.to_string()),
+                Cell::from(parent),
+                Cell::from(e.name.clone()),
+                Cell::from(lifetime),
+                Cell::from(format_bytes(e.peak_memory)),
+            ])
+        })
+        .collect();
+
+    let header = Row::new(vec![
+        "Time", "Event", "PID", "Parent", "Name", "Lifetime", "Peak RSS",
+    ])
+    .style(theme.header)
+    .bottom_margin(1);
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&[
+            Constraint::Length(10),     // TIME
+            Constraint::Length(7),      // EVENT
+            Constraint::Length(9),      // PID
+            Constraint::Length(9),      // PARENT
+            Constraint::Percentage(40), // NAME
+            Constraint::Length(10),     // LIFETIME
+            Constraint::Length(12),     // PEAK RSS
+        ])
+        .column_spacing(2)
+        .style(theme.text);
+    f.render_widget(table, table_area);
+    app.events_scroll = scroll;
+}
@@ -0,0 +1,225 @@
+use super::events::clock;
+use super::{help, table_columns, titled_block};
+use crate::fleet::{FleetRow, FleetView, HostState, FLEET_COLUMNS};
+use crate::keys::{Action, Keymap};
+use crate::theme::Theme;
+use std::time::UNIX_EPOCH;
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::Modifier,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+const HINTS: [(Action, &str); 7] = [
+    (Action::Down, "select"),
+    (Action::Details, "open"),
+    (Action::Search, "filter"),
+    (Action::SortNext, "sort"),
+    (Action::SortReverse, "reverse"),
+    (Action::ToggleMouse, "mouse"),
+    (Action::Quit, "quit"),
+];
+
+fn status_cell(row: &FleetRow, theme: &Theme) -> Span<'static> {
+    match row.state {
+        HostState::Up => Span::styled("up", theme.ok),
+        HostState::Stale => {
+            let seen = row
+                .last_seen
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs());
+            Span::styled(format!("stale, seen {}", clock(seen)), theme.warning)
+        }
+        HostState::Unreachable if row.error.is_some() => {
+            Span::styled("unreachable", theme.critical)
+        }
+        HostState::Unreachable => Span::styled("connecting", theme.text),
+    }
+}
+
+fn alerts_cell(row: &FleetRow, theme: &Theme) -> Span<'static> {
+    if row.firing > 0 {
+        Span::styled(
+            format!("{} firing", row.firing),
+            theme.critical.add_modifier(Modifier::BOLD),
+        )
+    } else if row.pending > 0 {
+        Span::styled(format!("{} pending", row.pending), theme.warning)
+    } else {
+        Span::styled("ok", theme.ok)
+    }
+}
+
+fn row_cells(row: &FleetRow, theme: &Theme) -> Vec<Cell<'static>> {
+    let mut cells = vec![
+        Cell::from(row.name.clone()),
+        Cell::from(row.address.clone()),
+        Cell::from(status_cell(row, theme)),
+    ];
+    // Never-reached hosts have nothing to show past their status
+    if row.state == HostState::Unreachable {
+        cells.extend((0..5).map(|_| Cell::from("-")));
+        return cells;
+    }
+    let disk = match &row.disk {
+        // Usage first so a long mount point is what gets cut off
+        Some((mount, usage)) => theme.severity(format!("{:.1}% {}", usage, mount), *usage),
+        None => Span::raw("-"),
+    };
+    cells.extend([
+        Cell::from(theme.severity(format!("{:.1}%", row.cpu), row.cpu)),
+        Cell::from(theme.severity(format!("{:.1}%", row.memory), row.memory)),
+        Cell::from(format!(
+            "{:.2} {:.2} {:.2}",
+            row.load[0], row.load[1], row.load[2]
+        )),
+        Cell::from(disk),
+        Cell::from(alerts_cell(row, theme)),
+    ]);
+    cells
+}
+
+// One row per configured agent; `rows` is already filtered and sorted
+pub fn draw_fleet<B: Backend>(
+    f: &mut Frame<B>,
+    view: &mut FleetView,
+    rows: &[FleetRow],
+    total: usize,
+    theme: &Theme,
+    keymap: &Keymap,
+    mouse_capture: bool,
+) {
+    let outer_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(f.size());
+    help::draw_hints(f, keymap, theme, &HINTS, outer_chunks[1]);
+
+    let count = |state: HostState| rows.iter().filter(|r| r.state == state).count();
+    let firing = rows.iter().filter(|r| r.firing > 0).count();
+    let block = titled_block(
+        format!(
+            "Fleet: {} of {} hosts, {} up, {} stale, {} unreachable, {} firing",
+            rows.len(),
+            total,
+            count(HostState::Up),
+            count(HostState::Stale),
+            count(HostState::Unreachable),
+            firing
+        ),
+        theme,
+    );
+    let area = outer_chunks[0];
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+    // Remind users why clicks stopped working after turning capture off
+    if !mouse_capture {
+        let note = Paragraph::new(Span::styled("mouse off (m)", theme.warning))
+            .alignment(Alignment::Right);
+        // On the top border, short of the corner
+        let top = Rect {
+            width: area.width.saturating_sub(2),
+            height: 1,
+            ..area
+        };
+        f.render_widget(note, top);
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .horizontal_margin(3)
+        .vertical_margin(1)
+        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(inner_area);
+
+    // Keep the selected row inside the visible window (header takes 2 lines)
+    let visible = chunks[0].height.saturating_sub(2).max(1) as usize;
+    let selected = view.selected_position(rows);
+    if let Some(selected) = selected {
+        if selected < view.scroll {
+            view.scroll = selected;
+        } else if selected >= view.scroll + visible {
+            view.scroll = selected + 1 - visible;
+        }
+    }
+    view.scroll = view.scroll.min(rows.len().saturating_sub(visible));
+
+    let table_rows: Vec<Row> = rows
+        .iter()
+        .skip(view.scroll)
+        .take(visible)
+        .map(|row| {
+            let style = match row.state {
+                HostState::Up => theme.text,
+                _ => theme.text.add_modifier(Modifier::DIM),
+            };
+            Row::new(row_cells(row, theme)).style(style)
+        })
+        .collect();
+
+    let header = Row::new(
+        FLEET_COLUMNS
+            .iter()
+            .map(|column| {
+                if *column != view.sort {
+                    column.title().to_string()
+                } else if view.descending {
+                    format!("{} ▼", column.title())
+                } else {
+                    format!("{} ▲", column.title())
+                }
+            })
+            .collect::<Vec<_>>(),
+    )
+    .style(theme.header)
+    .bottom_margin(1);
+
+    let widths: Vec<Constraint> = FLEET_COLUMNS
+        .iter()
+        .map(|column| Constraint::Length(column.width()))
+        .collect();
+    let table = Table::new(table_rows)
+        .header(header)
+        .block(Block::default().borders(Borders::NONE))
+        .widths(&widths)
+        .column_spacing(2)
+        .highlight_style(theme.selection)
+        .highlight_symbol(">>");
+
+    let mut state = TableState::default();
+    state.select(selected.map(|i| i - view.scroll));
+    let symbol_width = if state.selected().is_some() { 2 } else { 0 };
+    view.header = Rect {
+        height: 1,
+        ..chunks[0]
+    };
+    view.columns = table_columns(chunks[0], &widths, 2, symbol_width);
+    view.rows = Rect {
+        y: chunks[0].y + 2,
+        height: chunks[0].height.saturating_sub(2),
+        ..chunks[0]
+    };
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    // The filter while one is set, else why the selected host is not
+    // answering
+    let search = &view.search;
+    let line = if search.editing {
+        Span::styled(format!("/{}_", search.query), theme.header)
+    } else if !search.query.is_empty() {
+        Span::styled(format!("/{}", search.query), theme.header)
+    } else {
+        match selected.map(|i| &rows[i]) {
+            Some(FleetRow {
+                address,
+                error: Some(error),
+                state: HostState::Stale | HostState::Unreachable,
+                ..
+            }) => Span::styled(format!("{}: {}", address, error), theme.warning),
+            _ => Span::styled("/ to filter by host name or address", theme.text),
+        }
+    };
+    f.render_widget(Paragraph::new(Spans::from(line)), chunks[1]);
+}
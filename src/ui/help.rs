@@ -0,0 +1,174 @@
+use super::{centered, titled_block};
+use crate::app::{App, DetailTab, ProcessView, Tab};
+use crate::keys::{Action, Keymap, ACTIONS};
+use crate::theme::Theme;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Span, Spans},
+    widgets::{Clear, Paragraph, Wrap},
+    Frame,
+};
+
+// The most useful actions for whatever currently has focus, plus the way
+// back to the fleet view where Esc has nothing else to close
+fn footer_hints(app: &App) -> Vec<(Action, &'static str)> {
+    let mut hints = view_hints(app);
+    if app.in_fleet && !hints.iter().any(|(action, _)| *action == Action::Back) {
+        let help = hints.len().saturating_sub(2);
+        hints.insert(help, (Action::Back, "fleet"));
+    }
+    hints
+}
+
+fn view_hints(app: &App) -> Vec<(Action, &'static str)> {
+    if app.show_help {
+        return vec![(Action::Help, "close help"), (Action::Quit, "quit")];
+    }
+    if app.thread_view.is_some() {
+        return vec![
+            (Action::Down, "select"),
+            (Action::SortNext, "sort"),
+            (Action::SortReverse, "reverse"),
+            (Action::Back, "close"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ];
+    }
+    if app.detail_pid.is_some() {
+        let mut hints = vec![(Action::NextTab, "next tab")];
+        if app.inspection.tab != DetailTab::Info {
+            hints.push((Action::Down, "scroll"));
+            hints.push((Action::Search, "search"));
+        }
+        hints.extend([
+            (Action::Threads, "threads"),
+            (Action::Back, "close"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ]);
+        return hints;
+    }
+    match (app.tab, app.process_view) {
+        (Tab::Overview | Tab::Processes, ProcessView::Processes) => vec![
+            (Action::Down, "select"),
+            (Action::Details, "details"),
+            (Action::Threads, "threads"),
+            (Action::TogglePin, "pin"),
+            (Action::SortNext, "sort"),
+            (Action::SortReverse, "reverse"),
+            (Action::ToggleGroups, "groups"),
+            (Action::NextTab, "next tab"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ],
+        (Tab::Overview | Tab::Processes, ProcessView::Groups) => vec![
+            (Action::ToggleGroups, "processes"),
+            (Action::NextTab, "next tab"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ],
+        (Tab::Cpu, _) if app.show_topology => vec![
+            (Action::Down, "scroll"),
+            (Action::Back, "cores"),
+            (Action::NextTab, "next tab"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ],
+        (Tab::Cpu, _) => vec![
+            (Action::Details, "topology"),
+            (Action::NextTab, "next tab"),
+            (Action::PreviousTab, "previous tab"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ],
+        (Tab::Events, _) => vec![
+            (Action::Down, "scroll"),
+            (Action::NextTab, "next tab"),
+            (Action::PreviousTab, "previous tab"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ],
+        (Tab::Connections, _) => vec![
+            (Action::Down, "select"),
+            (Action::Details, "owner"),
+            (Action::Search, "filter"),
+            (Action::NextTab, "next tab"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ],
+        _ => vec![
+            (Action::NextTab, "next tab"),
+            (Action::PreviousTab, "previous tab"),
+            (Action::ToggleMouse, "mouse"),
+            (Action::Help, "help"),
+            (Action::Quit, "quit"),
+        ],
+    }
+}
+
+pub fn draw_footer<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    draw_hints(f, &app.keymap, &app.theme, &footer_hints(app), area);
+}
+
+pub fn draw_hints<B: Backend>(
+    f: &mut Frame<B>,
+    keymap: &Keymap,
+    theme: &Theme,
+    hints: &[(Action, &str)],
+    area: Rect,
+) {
+    let mut spans = Vec::new();
+    // Actions the user has unbound are left out
+    for (action, label) in hints {
+        if let Some(key) = keymap.hint(*action) {
+            spans.push(Span::styled(format!(" {}", key), theme.header));
+            spans.push(Span::styled(format!(" {} ", label), theme.text));
+        }
+    }
+    f.render_widget(Paragraph::new(Spans::from(spans)), area);
+}
+
+pub fn draw_help<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let popup = centered(area, 60, 90);
+    app.regions.help = popup;
+    let theme = &app.theme;
+    let block = titled_block(String::from("Keys - ? to close"), theme);
+    let inner_area = block.inner(popup);
+    let content = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(2)
+        .vertical_margin(1)
+        .constraints([Constraint::Percentage(100)].as_ref())
+        .split(inner_area)[0];
+
+    let mut lines: Vec<Spans> = ACTIONS
+        .iter()
+        .map(|(action, _, description)| {
+            let keys: Vec<String> = app
+                .keymap
+                .keys_for(*action)
+                .iter()
+                .map(|key| key.to_string())
+                .collect();
+            let keys = if keys.is_empty() {
+                String::from("(unbound)")
+            } else {
+                keys.join(", ")
+            };
+            Spans::from(vec![
+                Span::styled(format!("{:<16}", keys), theme.header),
+                Span::styled(description.to_string(), theme.text),
+            ])
+        })
+        .collect();
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(Span::styled(
+        "Mouse: click to select, sort or switch tabs; double-click for details; wheel to scroll",
+        theme.text,
+    )));
+
+    f.render_widget(Clear, popup);
+    f.render_widget(block, popup);
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), content);
+}
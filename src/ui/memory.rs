@@ -0,0 +1,203 @@
+use super::{draw_history_chart, format_bytes, stacked_bar, titled_block};
+use crate::app::App;
+use crate::system::{MemInfo, SystemStats};
+use crate::theme::Theme;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Span, Spans},
+    widgets::{Gauge, Paragraph},
+    Frame,
+};
+
+fn percent(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        used as f64 / total as f64 * 100.0
+    }
+}
+
+// How RAM is divided, as (label, bytes, style, fill character)
+fn ram_segments(theme: &Theme, meminfo: &MemInfo) -> [(&'static str, u64, Style, char); 4] {
+    [
+        ("Used", meminfo.used(), theme.critical, '█'),
+        ("Buffers", meminfo.buffers, theme.warning, '█'),
+        ("Cache", meminfo.cache(), theme.chart, '█'),
+        ("Free", meminfo.free, theme.text, '░'),
+    ]
+}
+
+fn ram_bar(theme: &Theme, meminfo: &MemInfo, width: u16) -> Spans<'static> {
+    let segments: Vec<(f64, Style, char)> = ram_segments(theme, meminfo)
+        .iter()
+        .map(|(_, bytes, style, fill)| (*bytes as f64, *style, *fill))
+        .collect();
+    Spans::from(stacked_bar(&segments, meminfo.total as f64, width as usize))
+}
+
+fn bar_legend(theme: &Theme, meminfo: &MemInfo) -> Spans<'static> {
+    let mut spans = Vec::new();
+    for (label, bytes, style, fill) in ram_segments(theme, meminfo) {
+        spans.push(Span::styled(format!("{} ", fill), style));
+        spans.push(Span::styled(
+            format!("{} {}   ", label, format_bytes(bytes)),
+            theme.text,
+        ));
+    }
+    Spans::from(spans)
+}
+
+fn detail_line(theme: &Theme, label: &str, value: String) -> Spans<'static> {
+    Spans::from(vec![
+        Span::styled(format!("{:<16}", label), theme.text),
+        Span::styled(format!("{:>12}", value), theme.text),
+    ])
+}
+
+// Two columns of /proc/meminfo figures
+fn meminfo_columns(theme: &Theme, meminfo: &MemInfo) -> [Vec<Spans<'static>>; 2] {
+    let huge_pages = if meminfo.huge_pages_total == 0 {
+        String::from("none")
+    } else {
+        format!(
+            "{}/{} x {}",
+            meminfo.huge_pages_free,
+            meminfo.huge_pages_total,
+            format_bytes(meminfo.huge_page_size)
+        )
+    };
+    [
+        vec![
+            detail_line(theme, "Total", format_bytes(meminfo.total)),
+            detail_line(theme, "Available", format_bytes(meminfo.available)),
+            detail_line(theme, "Used", format_bytes(meminfo.used())),
+            detail_line(theme, "Free", format_bytes(meminfo.free)),
+            detail_line(theme, "Buffers", format_bytes(meminfo.buffers)),
+            detail_line(theme, "Cached", format_bytes(meminfo.cached)),
+            detail_line(theme, "Shared", format_bytes(meminfo.shared)),
+        ],
+        vec![
+            detail_line(
+                theme,
+                "Slab (reclaim)",
+                format_bytes(meminfo.slab_reclaimable),
+            ),
+            detail_line(
+                theme,
+                "Slab (unreclaim)",
+                format_bytes(meminfo.slab_unreclaimable),
+            ),
+            detail_line(theme, "Dirty", format_bytes(meminfo.dirty)),
+            detail_line(theme, "Writeback", format_bytes(meminfo.writeback)),
+            detail_line(
+                theme,
+                "Committed",
+                format!(
+                    "{} / {}",
+                    format_bytes(meminfo.committed),
+                    format_bytes(meminfo.commit_limit)
+                ),
+            ),
+            detail_line(theme, "Swap cached", format_bytes(meminfo.swap_cached)),
+            detail_line(theme, "Huge pages free", huge_pages),
+        ],
+    ]
+}
+
+pub fn draw_memory_view<B: Backend>(
+    f: &mut Frame<B>,
+    app: &App,
+    stats: &SystemStats,
+    meminfo: Option<&MemInfo>,
+    area: Rect,
+) {
+    let theme = &app.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(16), Constraint::Min(0)].as_ref())
+        .split(area);
+
+    let block = titled_block(String::from("Memory"), theme);
+    let inner_area = block.inner(chunks[0]);
+    f.render_widget(block, chunks[0]);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .horizontal_margin(2)
+        .constraints(
+            [
+                Constraint::Length(1), // memory gauge
+                Constraint::Length(1), // swap gauge
+                Constraint::Length(1),
+                Constraint::Length(1), // RAM breakdown bar
+                Constraint::Length(1), // legend
+                Constraint::Length(1),
+                Constraint::Min(0), // details
+            ]
+            .as_ref(),
+        )
+        .split(inner_area);
+
+    let gauges = [
+        ("Memory", stats.used_memory, stats.total_memory),
+        ("Swap", stats.used_swap, stats.total_swap),
+    ];
+    for (i, (label, used, total)) in gauges.into_iter().enumerate() {
+        let pct = percent(used, total);
+        let style = theme.severity(String::new(), pct as f32).style;
+        let gauge = Gauge::default()
+            .gauge_style(style)
+            .ratio((pct / 100.0).clamp(0.0, 1.0))
+            .label(Span::styled(
+                format!(
+                    "{} {:.2}% ({} / {})",
+                    label,
+                    pct,
+                    format_bytes(used),
+                    format_bytes(total)
+                ),
+                theme.text,
+            ));
+        f.render_widget(gauge, rows[i]);
+    }
+
+    match meminfo {
+        Some(meminfo) => {
+            let bar = ram_bar(theme, meminfo, rows[3].width);
+            f.render_widget(Paragraph::new(bar), rows[3]);
+            f.render_widget(Paragraph::new(bar_legend(theme, meminfo)), rows[4]);
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(34), Constraint::Min(0)].as_ref())
+                .split(rows[6]);
+            for (lines, column) in meminfo_columns(theme, meminfo).into_iter().zip(columns) {
+                f.render_widget(Paragraph::new(lines), column);
+            }
+        }
+        // Without /proc/meminfo, fall back to what sysinfo reports
+        None => {
+            let lines = vec![
+                detail_line(theme, "Total", format_bytes(stats.total_memory)),
+                detail_line(theme, "Available", format_bytes(stats.available_memory)),
+                detail_line(theme, "Used", format_bytes(stats.used_memory)),
+                detail_line(theme, "Free", format_bytes(stats.free_memory)),
+                detail_line(theme, "Swap Used", format_bytes(stats.used_swap)),
+            ];
+            f.render_widget(Paragraph::new(lines), rows[6]);
+        }
+    }
+
+    draw_history_chart(
+        f,
+        theme,
+        String::from("Memory / Swap History (GB)"),
+        &[
+            ("Memory", &app.history.memory, theme.chart),
+            ("Swap", &app.history.swap, theme.warning),
+        ],
+        1000000000.0,
+        stats.total_memory.max(stats.total_swap) as f64 / 1000000000.0,
+        chunks[1],
+    );
+}
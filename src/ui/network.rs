@@ -0,0 +1,92 @@
+use super::{draw_history_chart, format_bytes, titled_block};
+use crate::app::App;
+use crate::system::NetworkInterface;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    widgets::{Block, Borders, Cell, Row, Table},
+    Frame,
+};
+
+pub fn draw_network_view<B: Backend>(
+    f: &mut Frame<B>,
+    app: &App,
+    networks: &[NetworkInterface],
+    area: Rect,
+) {
+    let theme = &app.theme;
+    let table_height = (networks.len() as u16 + 6).min(area.height / 2);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(table_height), Constraint::Min(0)].as_ref())
+        .split(area);
+
+    let block = titled_block(format!("Interfaces ({})", networks.len()), theme);
+    let inner_area = block.inner(chunks[0]);
+    f.render_widget(block, chunks[0]);
+    let network_margined_chunk = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(3)
+        .vertical_margin(1)
+        .constraints([Constraint::Percentage(100)].as_ref())
+        .split(inner_area)[0];
+
+    let mut rows = Vec::new();
+    for n in networks {
+        rows.push(Row::new(vec![
+            Cell::from(n.name.clone()),
+            Cell::from(format!("{}/s", format_bytes(n.rx_rate))),
+            Cell::from(format!("{}/s", format_bytes(n.tx_rate))),
+            Cell::from(format_bytes(n.total_rx)),
+            Cell::from(format_bytes(n.total_tx)),
+            Cell::from(format!("{}/{}", n.total_rx_packets, n.total_tx_packets)),
+            Cell::from(n.total_errors.to_string()),
+            Cell::from(n.mac.clone()),
+            Cell::from(n.addresses.join(", ")),
+        ]));
+    }
+
+    let header = Row::new(vec![
+        "Interface",
+        "RX",
+        "TX",
+        "Total RX",
+        "Total TX",
+        "Packets RX/TX",
+        "Errors",
+        "MAC",
+        "Addresses",
+    ])
+    .style(theme.header)
+    .bottom_margin(1);
+
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::NONE))
+        .widths(&[
+            Constraint::Percentage(10), // INTERFACE
+            Constraint::Percentage(10), // RX
+            Constraint::Percentage(10), // TX
+            Constraint::Percentage(9),  // TOTAL RX
+            Constraint::Percentage(9),  // TOTAL TX
+            Constraint::Percentage(13), // PACKETS
+            Constraint::Percentage(6),  // ERRORS
+            Constraint::Percentage(13), // MAC
+            Constraint::Percentage(20), // ADDRESSES
+        ])
+        .column_spacing(1);
+    f.render_widget(table, network_margined_chunk);
+
+    draw_history_chart(
+        f,
+        theme,
+        String::from("Throughput, all interfaces (KB/s)"),
+        &[
+            ("RX", &app.history.rx, theme.chart),
+            ("TX", &app.history.tx, theme.warning),
+        ],
+        1000.0,
+        1.0,
+        chunks[1],
+    );
+}
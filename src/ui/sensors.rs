@@ -0,0 +1,76 @@
+use super::titled_block;
+use crate::system::SensorReading;
+use crate::theme::Theme;
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    text::Span,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    Frame,
+};
+
+fn format_temperature(value: Option<f32>) -> String {
+    match value {
+        Some(value) => format!("{:.1} °C", value),
+        None => String::from("-"),
+    }
+}
+
+pub fn draw_sensors_view<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    sensors: &[SensorReading],
+    area: Rect,
+) {
+    let block = titled_block(format!("Sensors ({})", sensors.len()), theme);
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+    let sensors_margined_chunk = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(3)
+        .vertical_margin(1)
+        .constraints([Constraint::Percentage(100)].as_ref())
+        .split(inner_area)[0];
+
+    if sensors.is_empty() {
+        let note = Paragraph::new(Span::styled("No temperature sensors found", theme.text))
+            .alignment(Alignment::Left);
+        f.render_widget(note, sensors_margined_chunk);
+        return;
+    }
+
+    let mut rows = Vec::new();
+    for sensor in sensors {
+        // Coloured against the critical threshold when the sensor reports one
+        let temperature = format_temperature(sensor.temperature);
+        let temperature = match (sensor.temperature, sensor.critical) {
+            (Some(value), Some(critical)) if critical > 0.0 => {
+                Cell::from(theme.severity(temperature, value / critical * 100.0))
+            }
+            _ => Cell::from(temperature),
+        };
+        rows.push(Row::new(vec![
+            Cell::from(sensor.label.clone()),
+            temperature,
+            Cell::from(format_temperature(sensor.max)),
+            Cell::from(format_temperature(sensor.critical)),
+        ]));
+    }
+
+    let header = Row::new(vec!["Sensor", "Temperature", "Max", "Critical"])
+        .style(theme.header)
+        .bottom_margin(1);
+
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::NONE))
+        .widths(&[
+            Constraint::Percentage(46), // SENSOR
+            Constraint::Percentage(18), // TEMPERATURE
+            Constraint::Percentage(18), // MAX
+            Constraint::Percentage(18), // CRITICAL
+        ])
+        .column_spacing(2);
+
+    f.render_widget(table, sensors_margined_chunk);
+}
@@ -0,0 +1,125 @@
+use super::{centered, titled_block};
+use crate::app::App;
+use crate::processes::ProcessInfo;
+use crate::threads::ThreadSort;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::Span,
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+const THREAD_COLUMNS: [ThreadSort; 3] = [ThreadSort::Tid, ThreadSort::Name, ThreadSort::Cpu];
+
+pub fn draw_threads<B: Backend>(
+    f: &mut Frame<B>,
+    app: &mut App,
+    processes: &[ProcessInfo],
+    area: Rect,
+) {
+    let popup = centered(area, 70, 70);
+    app.regions.threads = popup;
+    let theme = &app.theme;
+    let (cpu_mode, cpu_count) = (app.cpu_mode, app.cpu_count);
+    let Some(view) = &mut app.thread_view else {
+        return;
+    };
+
+    let pid = view.sampler.pid();
+    let title = match processes.iter().find(|p| p.pid.as_u32() == pid) {
+        Some(p) => format!(
+            "Threads of {} ({}): {} - Esc to close",
+            pid,
+            p.name,
+            view.threads.len()
+        ),
+        None => format!("Threads of {} - Esc to close", pid),
+    };
+    let block = titled_block(title, theme);
+    let inner_area = block.inner(popup);
+    let content = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(2)
+        .vertical_margin(1)
+        .constraints([Constraint::Percentage(100)].as_ref())
+        .split(inner_area)[0];
+    f.render_widget(Clear, popup);
+    f.render_widget(block, popup);
+
+    if view.threads.is_empty() {
+        let message = Span::styled("The process has exited", theme.warning);
+        f.render_widget(Paragraph::new(message), content);
+        return;
+    }
+
+    // Keep the selected row inside the visible window (header takes 2 lines)
+    let visible = content.height.saturating_sub(2).max(1) as usize;
+    let selected = view.selected_index();
+    if let Some(selected) = selected {
+        if selected < view.scroll {
+            view.scroll = selected;
+        } else if selected >= view.scroll + visible {
+            view.scroll = selected + 1 - visible;
+        }
+    }
+    view.scroll = view.scroll.min(view.threads.len().saturating_sub(visible));
+
+    let rows: Vec<Row> = view
+        .threads
+        .iter()
+        .skip(view.scroll)
+        .take(visible)
+        .map(|t| {
+            // Colour by the share of a core whichever way it is shown
+            let cpu = cpu_mode.scale(t.cpu, cpu_count);
+            Row::new(vec![
+                Cell::from(t.tid.to_string()),
+                Cell::from(t.name.clone()),
+                Cell::from(theme.severity(format!("{:.2}%", cpu), t.cpu)),
+                Cell::from(t.state.to_string()),
+                Cell::from(t.affinity.clone()),
+            ])
+        })
+        .collect();
+
+    // Sortable columns first, with an arrow on the sorted one
+    let mut titles: Vec<String> = THREAD_COLUMNS
+        .iter()
+        .map(|column| {
+            if *column != view.sort {
+                column.title().to_string()
+            } else if view.descending {
+                format!("{} ▼", column.title())
+            } else {
+                format!("{} ▲", column.title())
+            }
+        })
+        .collect();
+    titles.push(String::from("State"));
+    titles.push(String::from("Affinity"));
+    let header = Row::new(titles).style(theme.header).bottom_margin(1);
+
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::NONE))
+        .widths(&[
+            Constraint::Length(8),      // TID
+            Constraint::Percentage(30), // NAME
+            Constraint::Length(9),      // CPU
+            Constraint::Length(6),      // STATE
+            Constraint::Percentage(30), // AFFINITY
+        ])
+        .column_spacing(2)
+        .highlight_style(theme.selection)
+        .highlight_symbol(">>");
+
+    let mut state = TableState::default();
+    state.select(selected.map(|i| i - view.scroll));
+    app.regions.thread_rows = Rect {
+        y: content.y + 2,
+        height: content.height.saturating_sub(2),
+        ..content
+    };
+    f.render_stateful_widget(table, content, &mut state);
+}